@@ -0,0 +1,159 @@
+use crate::mania::structs::Behavior;
+use crate::resolver::{AssetResolver, ResolveOptions, ResolvedAsset};
+use crate::structs::SkinMetadata;
+
+/// How fast an animation plays.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrameRate {
+    /// A fixed number of frames per second.
+    PerSecond(f64),
+    /// Every frame in one second, whatever their count.
+    OneSecond,
+}
+
+impl FrameRate {
+    /// `AnimationFramerate` of `[General]`, used by most animated elements. osu! plays the
+    /// whole animation in one second when it is not positive.
+    pub fn general(metadata: &SkinMetadata) -> Self {
+        match metadata.animation_framerate {
+            rate if rate > 0 => FrameRate::PerSecond(rate as f64),
+            _ => FrameRate::OneSecond,
+        }
+    }
+
+    /// `LightFramePerSecond` of a `[Mania]` block, for `mania-stage-light`.
+    pub fn mania_light(behavior: &Behavior) -> Self {
+        match behavior.light_frame_per_second {
+            0 => FrameRate::OneSecond,
+            rate => FrameRate::PerSecond(rate as f64),
+        }
+    }
+
+    /// Time each of `frame_count` frames stays on screen, in milliseconds.
+    pub fn frame_duration(self, frame_count: usize) -> f64 {
+        match self {
+            FrameRate::PerSecond(rate) => 1000.0 / rate,
+            FrameRate::OneSecond => 1000.0 / frame_count.max(1) as f64,
+        }
+    }
+}
+
+/// The frames osu! shows for an element, see `AssetResolver::animation`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animation {
+    /// In play order. A single frame for a static element.
+    pub frames: Vec<ResolvedAsset>,
+    /// Whether the frames are an animated set (`name-0`, `name-1`, ...).
+    pub animated: bool,
+    /// Time each frame stays on screen, in milliseconds.
+    pub frame_duration: f64,
+}
+
+impl Animation {
+    /// Time of a whole loop, in milliseconds.
+    pub fn duration(&self) -> f64 {
+        self.frame_duration * self.frames.len() as f64
+    }
+
+    /// The frame shown `time` milliseconds after the start, looping.
+    pub fn frame_at(&self, time: f64) -> &ResolvedAsset {
+        let index = if self.frame_duration > 0.0 && time > 0.0 {
+            (time / self.frame_duration) as usize % self.frames.len()
+        } else {
+            0
+        };
+        &self.frames[index]
+    }
+}
+
+impl AssetResolver {
+    /// The frames of the element `name`: `name-0`, `name-1`, ... up to the first missing
+    /// frame, or the static `name` when there is no `name-0`. Like osu!, the animated set
+    /// wins when both exist. `None` when neither does.
+    pub fn animation(
+        &self,
+        name: &str,
+        rate: FrameRate,
+        options: ResolveOptions,
+    ) -> Option<Animation> {
+        let frames: Vec<ResolvedAsset> = (0..)
+            .map_while(|frame| self.resolve(&format!("{}-{}", name, frame), options))
+            .collect();
+        let animated = !frames.is_empty();
+        let frames = if animated {
+            frames
+        } else {
+            vec![self.resolve(name, options)?]
+        };
+        Some(Animation {
+            frame_duration: rate.frame_duration(frames.len()),
+            frames,
+            animated,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(animation: &Animation) -> Vec<&str> {
+        animation
+            .frames
+            .iter()
+            .map(|frame| frame.path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_animation() {
+        let resolver = AssetResolver::from_paths([
+            "mania-hit300.png",
+            "mania-hit300-0.png",
+            "mania-hit300-1@2x.png",
+            "mania-hit300-1.png",
+            "mania-hit300-3.png",
+            "mania-hit0.png",
+        ]);
+        let options = ResolveOptions {
+            high_resolution: true,
+            ..ResolveOptions::default()
+        };
+
+        // The animated set wins, up to the first gap
+        let hit = resolver
+            .animation("mania-hit300", FrameRate::OneSecond, options)
+            .unwrap();
+        assert!(hit.animated);
+        assert_eq!(paths(&hit), ["mania-hit300-0.png", "mania-hit300-1@2x.png"]);
+        assert_eq!(hit.frame_duration, 500.0);
+        assert_eq!(hit.frame_at(750.0).path, "mania-hit300-1@2x.png");
+        assert_eq!(hit.frame_at(1250.0).path, "mania-hit300-0.png");
+
+        let miss = resolver
+            .animation("mania-hit0", FrameRate::PerSecond(20.0), options)
+            .unwrap();
+        assert!(!miss.animated);
+        assert_eq!(paths(&miss), ["mania-hit0.png"]);
+        assert_eq!(miss.duration(), 50.0);
+
+        assert!(resolver
+            .animation("mania-hit50", FrameRate::OneSecond, options)
+            .is_none());
+    }
+
+    #[test]
+    fn test_frame_rate() {
+        let mut metadata = SkinMetadata::default();
+        assert_eq!(FrameRate::general(&metadata), FrameRate::OneSecond);
+        metadata.animation_framerate = 40;
+        assert_eq!(FrameRate::general(&metadata).frame_duration(3), 25.0);
+        assert_eq!(FrameRate::OneSecond.frame_duration(4), 250.0);
+
+        let config = crate::mania::parser::default_config(4).unwrap();
+        assert_eq!(
+            FrameRate::mania_light(&config.behavior),
+            FrameRate::PerSecond(24.0)
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+
+use crate::parser::normalize_asset_path;
+
+/// The files of a skin kept in memory, keyed by their path inside the skin folder.
+///
+/// Paths use `/` and keep their case. Lookups with `find` ignore case and accept `\`,
+/// like osu! on Windows.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AssetStore {
+    files: BTreeMap<String, Vec<u8>>,
+    /// Lowercased path to the path as stored.
+    index: BTreeMap<String, String>,
+}
+
+impl AssetStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds or replaces the file at `path`.
+    pub fn insert(&mut self, path: &str, bytes: Vec<u8>) {
+        let path = normalize_asset_path(path);
+        if let Some(previous) = self.index.insert(path.to_lowercase(), path.clone()) {
+            self.files.remove(&previous);
+        }
+        self.files.insert(path, bytes);
+    }
+
+    /// Removes the file matching `path`, ignoring case, and returns its content.
+    pub fn remove(&mut self, path: &str) -> Option<Vec<u8>> {
+        let stored = self
+            .index
+            .remove(&normalize_asset_path(path).to_lowercase())?;
+        self.files.remove(&stored)
+    }
+
+    /// The file stored at exactly `path`.
+    pub fn get(&self, path: &str) -> Option<&[u8]> {
+        self.files.get(path).map(Vec::as_slice)
+    }
+
+    /// The file matching `path` ignoring case and separators, with its stored path.
+    pub fn find(&self, path: &str) -> Option<(&str, &[u8])> {
+        let stored = self.index.get(&normalize_asset_path(path).to_lowercase())?;
+        self.files
+            .get_key_value(stored)
+            .map(|(path, bytes)| (path.as_str(), bytes.as_slice()))
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.find(path).is_some()
+    }
+
+    /// Every file, sorted by path.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.files
+            .iter()
+            .map(|(path, bytes)| (path.as_str(), bytes.as_slice()))
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Size of every file, in bytes.
+    pub fn total_size(&self) -> u64 {
+        self.files.values().map(|bytes| bytes.len() as u64).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asset_store() {
+        let mut assets = AssetStore::new();
+        assets.insert("Arrownote\\HoldBody.png", vec![1, 2, 3]);
+        assets.insert("mania-key1.png", vec![4]);
+
+        assert_eq!(assets.get("Arrownote/HoldBody.png"), Some(&[1, 2, 3][..]));
+        assert_eq!(assets.get("arrownote/holdbody.png"), None);
+        assert_eq!(
+            assets.find("arrownote\\holdbody.PNG"),
+            Some(("Arrownote/HoldBody.png", &[1, 2, 3][..]))
+        );
+        assert_eq!(assets.total_size(), 4);
+
+        // Same file with another case replaces it
+        assets.insert("MANIA-KEY1.png", vec![5, 6]);
+        assert_eq!(
+            assets.paths().collect::<Vec<_>>(),
+            vec!["Arrownote/HoldBody.png", "MANIA-KEY1.png"]
+        );
+        assert_eq!(assets.remove("./mania-key1.png"), Some(vec![5, 6]));
+        assert_eq!(assets.len(), 1);
+        assert!(!assets.contains("mania-key1.png"));
+    }
+}
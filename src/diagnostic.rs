@@ -0,0 +1,220 @@
+use std::fmt;
+
+use crate::error::SkinError;
+use crate::source::SourceMap;
+
+/// How parsers react to a problem in the file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseMode {
+    /// The first problem aborts parsing with a `SkinError`.
+    Strict,
+    /// Problems are recorded and the value osu! would use is kept, like the game does.
+    Lenient,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// The value could not be parsed, the default was used instead.
+    InvalidValue,
+    /// A colour with fewer than three components, the default was used instead.
+    IncompleteColour,
+    /// A per-column list with fewer items than columns, padded with defaults.
+    ShortList { expected: usize, found: usize },
+    /// The key appears more than once in its section, the last value wins.
+    DuplicateKey,
+    /// A key osu! does not read, ignored.
+    UnknownKey,
+    /// A per-column key past the last column, ignored.
+    ColumnOutOfRange { column: u32, key_count: u32 },
+    /// A `[` line that is not a header osu! recognises, e.g. `[Mania` or an indented
+    /// header, read as an unknown key.
+    MalformedHeader { column: usize },
+    /// A line without `:`, read as a key with an empty value.
+    MissingColon { column: usize },
+}
+
+/// A problem found while parsing, along with the entry it comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub section: String,
+    pub key: String,
+    pub value: String,
+    pub line: Option<usize>,
+    pub kind: DiagnosticKind,
+}
+
+impl Diagnostic {
+    pub fn new(section: &str, key: &str, value: &str, kind: DiagnosticKind) -> Self {
+        Self {
+            section: section.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+            line: None,
+            kind,
+        }
+    }
+
+    /// The error strict mode reports for this problem.
+    pub fn into_error(self) -> SkinError {
+        let Diagnostic {
+            section,
+            key,
+            value,
+            line,
+            kind,
+        } = self;
+        match kind {
+            DiagnosticKind::InvalidValue
+            | DiagnosticKind::IncompleteColour
+            | DiagnosticKind::ShortList { .. } => SkinError::InvalidValue {
+                section,
+                key,
+                value,
+                line,
+            },
+            DiagnosticKind::DuplicateKey => SkinError::DuplicateKey {
+                section,
+                key,
+                value,
+                line,
+            },
+            DiagnosticKind::UnknownKey => SkinError::UnknownKey {
+                section,
+                key,
+                value,
+                line,
+            },
+            DiagnosticKind::ColumnOutOfRange { column, key_count } => SkinError::ColumnOutOfRange {
+                section,
+                key,
+                value,
+                column,
+                key_count,
+                line,
+            },
+            DiagnosticKind::MalformedHeader { column } => SkinError::Syntax {
+                line: line.unwrap_or_default(),
+                column,
+                message: format!("malformed section header \"{}\"", key),
+            },
+            DiagnosticKind::MissingColon { column } => SkinError::Syntax {
+                line: line.unwrap_or_default(),
+                column,
+                message: format!("expected \"Key: Value\", found \"{}\"", key),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: ", self.section, self.key)?;
+        match &self.kind {
+            DiagnosticKind::InvalidValue => {
+                write!(f, "invalid value \"{}\", using the default", self.value)
+            }
+            DiagnosticKind::IncompleteColour => write!(
+                f,
+                "colour \"{}\" has fewer than three components, using the default",
+                self.value
+            ),
+            DiagnosticKind::ShortList { expected, found } => write!(
+                f,
+                "{} values for {} columns, padding with defaults",
+                found, expected
+            ),
+            DiagnosticKind::DuplicateKey => write!(f, "duplicate key, the last value is used"),
+            DiagnosticKind::UnknownKey => write!(f, "unknown key, ignored"),
+            DiagnosticKind::ColumnOutOfRange { column, key_count } => write!(
+                f,
+                "column {} is out of range for {} keys, ignored",
+                column, key_count
+            ),
+            DiagnosticKind::MalformedHeader { .. } => {
+                write!(f, "malformed section header, read as a key")
+            }
+            DiagnosticKind::MissingColon { .. } => {
+                write!(f, "no colon, read as a key with an empty value")
+            }
+        }?;
+        match self.line {
+            Some(line) => write!(f, " (line {})", line),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Collects the diagnostics of a parse, or turns the first one into an error in strict mode.
+///
+/// With a `SourceMap`, every diagnostic and error gets the line of its key.
+#[derive(Debug)]
+pub struct Diagnostics<'a> {
+    mode: ParseMode,
+    diagnostics: Vec<Diagnostic>,
+    source_map: Option<&'a SourceMap>,
+    occurrence: usize,
+}
+
+impl<'a> Diagnostics<'a> {
+    pub fn new(mode: ParseMode) -> Self {
+        Self {
+            mode,
+            diagnostics: Vec::new(),
+            source_map: None,
+            occurrence: 0,
+        }
+    }
+
+    pub fn with_source_map(mut self, source_map: &'a SourceMap) -> Self {
+        self.source_map = Some(source_map);
+        self
+    }
+
+    pub fn strict() -> Self {
+        Self::new(ParseMode::Strict)
+    }
+
+    pub fn lenient() -> Self {
+        Self::new(ParseMode::Lenient)
+    }
+
+    pub fn mode(&self) -> ParseMode {
+        self.mode
+    }
+
+    /// Tells which section of a given name is being parsed, so that lines are looked up
+    /// in the right `[Mania]` block. Counts from 0.
+    pub fn enter_section(&mut self, occurrence: usize) {
+        self.occurrence = occurrence;
+    }
+
+    /// Records `diagnostic`, or fails with it in strict mode.
+    pub fn report(&mut self, mut diagnostic: Diagnostic) -> Result<(), SkinError> {
+        if diagnostic.line.is_none() {
+            diagnostic.line = self
+                .source_map
+                .and_then(|map| map.section(&diagnostic.section, self.occurrence))
+                .and_then(|section| section.get(&diagnostic.key))
+                .map(|span| span.line);
+        }
+        match self.mode {
+            ParseMode::Strict => Err(diagnostic.into_error()),
+            ParseMode::Lenient => {
+                self.diagnostics.push(diagnostic);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.diagnostics.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    pub fn into_vec(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+}
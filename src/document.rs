@@ -0,0 +1,530 @@
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+
+use crate::encoding::{decode, TextEncoding};
+use crate::error::SkinError;
+use crate::ini::REPEATED_SECTIONS;
+use crate::lexer::{strip_comment, tokenize, tokenize_line, TokenKind};
+use crate::skin::Skin;
+
+const MANIA: &str = "Mania";
+
+/// A skin.ini kept line by line as written, for editing values without reformatting it.
+///
+/// Keys are looked up the way osu! reads them (see `tokenize`). Editing a value only
+/// rewrites that value: comments, blank lines, indentation, key order, line endings and
+/// the encoding of the file are kept, so an untouched document writes back byte for byte.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SkinDocument {
+    lines: Vec<Line>,
+    encoding: TextEncoding,
+    /// Whether the file started with a byte order mark.
+    bom: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Line {
+    text: String,
+    /// `\n`, `\r\n`, `\r`, or empty for a last line with no line ending.
+    ending: String,
+}
+
+/// Lines of one section block: after its header up to the next one.
+struct Block {
+    /// Line of the header, `None` for the keys written before any header.
+    header: Option<usize>,
+    lines: Range<usize>,
+}
+
+impl SkinDocument {
+    pub fn load(path: &str) -> Result<Self, SkinError> {
+        Ok(Self::from_bytes(&fs::read(path)?))
+    }
+
+    /// Reads a skin.ini in any encoding `decode` detects. `to_bytes` writes it back in the
+    /// same one.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let (content, encoding) = decode(bytes);
+        let mark = encoding.byte_order_mark();
+        Self {
+            encoding,
+            bom: !mark.is_empty() && bytes.starts_with(mark),
+            ..Self::load_from_str(&content)
+        }
+    }
+
+    pub fn load_from_str(content: &str) -> Self {
+        let bom = content.starts_with('\u{feff}');
+        let lines = tokenize(content)
+            .into_iter()
+            .map(|token| Line {
+                text: token.raw.to_string(),
+                ending: token.ending.to_string(),
+            })
+            .collect();
+        Self {
+            lines,
+            encoding: if bom {
+                TextEncoding::Utf8Bom
+            } else {
+                TextEncoding::Utf8
+            },
+            bom,
+        }
+    }
+
+    pub fn encoding(&self) -> TextEncoding {
+        self.encoding
+    }
+
+    /// The document in its original encoding, byte order mark included.
+    ///
+    /// When a legacy code page cannot hold an edited value, the whole document is written
+    /// as UTF-8 with a byte order mark instead, which osu! also reads.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let body = self.body();
+        let Some(encoded) = self.encoding.encode(&body) else {
+            let mark = TextEncoding::Utf8Bom.byte_order_mark();
+            return [mark, body.as_bytes()].concat();
+        };
+        let mut bytes = Vec::new();
+        if self.bom {
+            bytes.extend_from_slice(self.encoding.byte_order_mark());
+        }
+        bytes.extend(encoded);
+        bytes
+    }
+
+    /// Parses the document as it currently is, failing on the first invalid value.
+    pub fn to_skin(&self) -> Result<Skin, SkinError> {
+        self.body().parse()
+    }
+
+    /// Value of `key` as osu! reads it: the last one written in `section`.
+    ///
+    /// `[Mania]` blocks are separate, this only looks at the first one. See `get_mania`.
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.get_in(&self.section_blocks(section, None), key)
+    }
+
+    /// Sets `key` of `section` to `value`.
+    ///
+    /// The line osu! reads the key from is rewritten in place, keeping its indentation,
+    /// spacing and trailing comment. A new key goes after the last one of the section,
+    /// laid out like it, and a missing section is added at the end of the file.
+    ///
+    /// A value osu! would not read back whole, with a line break or a `//`, is rejected
+    /// as `InvalidValue`.
+    pub fn set(&mut self, section: &str, key: &str, value: &str) -> Result<(), SkinError> {
+        check_value(section, key, value)?;
+        let blocks = self.section_blocks(section, None);
+        if blocks.is_empty() {
+            self.push_section(section, key, value);
+        } else {
+            self.set_in(&blocks, key, value);
+        }
+        Ok(())
+    }
+
+    /// Removes every line setting `key` in `section`, `false` when there was none.
+    pub fn remove(&mut self, section: &str, key: &str) -> bool {
+        self.remove_in(&self.section_blocks(section, None), key)
+    }
+
+    /// Number of `[Mania]` headers in the file.
+    pub fn mania_count(&self) -> usize {
+        self.blocks()
+            .iter()
+            .filter(|(name, _)| *name == MANIA)
+            .count()
+    }
+
+    /// Index of the `[Mania]` block for `key_count` keys. When several blocks have the
+    /// same count, osu! uses the last one.
+    pub fn find_mania(&self, key_count: u32) -> Option<usize> {
+        (0..self.mania_count()).rev().find(|&index| {
+            self.get_mania(index, "Keys")
+                .and_then(|keys| keys.parse::<u32>().ok())
+                == Some(key_count)
+        })
+    }
+
+    /// Value of `key` in the `index`th `[Mania]` block of the file.
+    pub fn get_mania(&self, index: usize, key: &str) -> Option<&str> {
+        self.get_in(&self.section_blocks(MANIA, Some(index)), key)
+    }
+
+    /// Like `set`, in the `index`th `[Mania]` block. `false` when there is no such block.
+    pub fn set_mania(&mut self, index: usize, key: &str, value: &str) -> Result<bool, SkinError> {
+        check_value(MANIA, key, value)?;
+        let blocks = self.section_blocks(MANIA, Some(index));
+        if blocks.is_empty() {
+            return Ok(false);
+        }
+        self.set_in(&blocks, key, value);
+        Ok(true)
+    }
+
+    pub fn remove_mania(&mut self, index: usize, key: &str) -> bool {
+        self.remove_in(&self.section_blocks(MANIA, Some(index)), key)
+    }
+
+    /// Blocks of `section` in file order. Repeated sections only give their `occurrence`th
+    /// block, the first one by default.
+    fn section_blocks(&self, section: &str, occurrence: Option<usize>) -> Vec<Block> {
+        let blocks = self
+            .blocks()
+            .into_iter()
+            .filter(|(name, _)| *name == section)
+            .map(|(_, block)| block);
+        match (REPEATED_SECTIONS.contains(&section), occurrence) {
+            (true, None) => blocks.take(1).collect(),
+            (true, Some(occurrence)) => blocks.skip(occurrence).take(1).collect(),
+            (false, _) => blocks.collect(),
+        }
+    }
+
+    /// Every block of the file and its section name. Keys written before the first header
+    /// are a `[General]` block, like osu! reads them.
+    fn blocks(&self) -> Vec<(&str, Block)> {
+        let mut blocks = Vec::new();
+        let mut name = "General";
+        let mut header = None;
+        let mut start = 0;
+        for (index, line) in self.lines.iter().enumerate() {
+            if let TokenKind::Section(section) = tokenize_line(&line.text, index + 1).kind {
+                blocks.push((
+                    name,
+                    Block {
+                        header,
+                        lines: start..index,
+                    },
+                ));
+                name = section;
+                header = Some(index);
+                start = index + 1;
+            }
+        }
+        blocks.push((
+            name,
+            Block {
+                header,
+                lines: start..self.lines.len(),
+            },
+        ));
+
+        blocks.retain(|(_, block)| block.header.is_some() || self.has_entry(block.lines.clone()));
+        blocks
+    }
+
+    fn has_entry(&self, lines: Range<usize>) -> bool {
+        lines.into_iter().any(|index| self.key_at(index).is_some())
+    }
+
+    fn key_at(&self, index: usize) -> Option<&str> {
+        match tokenize_line(&self.lines[index].text, index + 1).kind {
+            TokenKind::Entry { key, .. } => Some(key),
+            _ => None,
+        }
+    }
+
+    /// Lines setting `key` in `blocks`, in file order.
+    fn entry_lines(&self, blocks: &[Block], key: &str) -> Vec<usize> {
+        blocks
+            .iter()
+            .flat_map(|block| block.lines.clone())
+            .filter(|&index| self.key_at(index) == Some(key))
+            .collect()
+    }
+
+    fn get_in(&self, blocks: &[Block], key: &str) -> Option<&str> {
+        let index = *self.entry_lines(blocks, key).last()?;
+        match tokenize_line(&self.lines[index].text, index + 1).kind {
+            TokenKind::Entry { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    fn set_in(&mut self, blocks: &[Block], key: &str, value: &str) {
+        if let Some(&index) = self.entry_lines(blocks, key).last() {
+            let line = &mut self.lines[index].text;
+            *line = replace_value(line, value);
+            return;
+        }
+
+        let Some(block) = blocks.last() else {
+            return;
+        };
+        let last_entry = block
+            .lines
+            .clone()
+            .rev()
+            .find(|&index| self.key_at(index).is_some());
+        // With no key in the block, follow the layout of the rest of the file
+        let (indent, separator) = last_entry
+            .or_else(|| (0..self.lines.len()).find(|&index| self.key_at(index).is_some()))
+            .and_then(|index| entry_layout(&self.lines[index].text))
+            .unwrap_or(("", ": "));
+        let text = format!("{}{}{}{}", indent, key, separator, value);
+
+        let anchor = last_entry.or(block.header);
+        let position = anchor.map_or(0, |index| index + 1);
+        self.insert_line(position, text);
+    }
+
+    fn remove_in(&mut self, blocks: &[Block], key: &str) -> bool {
+        let lines = self.entry_lines(blocks, key);
+        for &index in lines.iter().rev() {
+            let line = self.lines.remove(index);
+            // The last line keeps the file's ending, or lack of one
+            if index == self.lines.len() {
+                if let Some(last) = self.lines.last_mut() {
+                    last.ending = line.ending;
+                }
+            }
+        }
+        !lines.is_empty()
+    }
+
+    /// Appends `[section]` and its first key at the end of the file, after a blank line.
+    fn push_section(&mut self, section: &str, key: &str, value: &str) {
+        let position = self.lines.len();
+        let separated = self
+            .lines
+            .last()
+            .is_none_or(|line| line.text.trim().is_empty());
+        if !separated {
+            self.insert_line(position, String::new());
+        }
+        let position = self.lines.len();
+        self.insert_line(position, format!("[{}]", section));
+        let position = self.lines.len();
+        self.insert_line(position, format!("{}: {}", key, value));
+    }
+
+    /// Inserts a line before `position`, ended like the rest of the file.
+    fn insert_line(&mut self, position: usize, text: String) {
+        let ending = self.line_ending();
+        let ending = match position.checked_sub(1).map(|index| &mut self.lines[index]) {
+            // Inserted after a last line with no line ending: that line gets one instead
+            Some(previous) if previous.ending.is_empty() => {
+                previous.ending = ending;
+                String::new()
+            }
+            _ => ending,
+        };
+        self.lines.insert(position, Line { text, ending });
+    }
+
+    /// The first line ending of the file, `\n` when it has none.
+    fn line_ending(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.ending.as_str())
+            .find(|ending| !ending.is_empty())
+            .unwrap_or("\n")
+            .to_string()
+    }
+
+    fn body(&self) -> String {
+        let mut body = String::new();
+        for line in &self.lines {
+            body.push_str(&line.text);
+            body.push_str(&line.ending);
+        }
+        body
+    }
+}
+
+impl fmt::Display for SkinDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.bom {
+            f.write_str("\u{feff}")?;
+        }
+        f.write_str(&self.body())
+    }
+}
+
+/// Byte range of the value of an entry line, between the spacing after `:` and the
+/// spacing before an inline comment.
+fn value_range(line: &str) -> Option<Range<usize>> {
+    let code = strip_comment(line);
+    let after_colon = code.find(':')? + 1;
+    let rest = &code[after_colon..];
+    let start = after_colon + rest.len() - rest.trim_start().len();
+    let end = code.trim_end().len().max(start);
+    Some(start..end)
+}
+
+fn replace_value(line: &str, value: &str) -> String {
+    match value_range(line) {
+        Some(range) => format!("{}{}{}", &line[..range.start], value, &line[range.end..]),
+        // A key without `:` gets one
+        None => {
+            let end = strip_comment(line).trim_end().len();
+            format!("{}: {}{}", &line[..end], value, &line[end..])
+        }
+    }
+}
+
+/// Indentation of an entry line and what separates its key from its value.
+fn entry_layout(line: &str) -> Option<(&str, &str)> {
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let code = strip_comment(line);
+    let colon = code.find(':')?;
+    let key_end = code[..colon].trim_end().len();
+    let value_start = value_range(line)?.start;
+    // An empty value says nothing about the space after the colon
+    if value_start == code.len() {
+        return Some((indent, &code[key_end..=colon]));
+    }
+    Some((indent, &code[key_end..value_start]))
+}
+
+/// Rejects a value that would not stay on its line as written.
+fn check_value(section: &str, key: &str, value: &str) -> Result<(), SkinError> {
+    if value.contains(['\n', '\r']) || value.contains("//") {
+        return Err(SkinError::InvalidValue {
+            section: section.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+            line: None,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn test_unchanged_round_trip() -> Result<(), Box<dyn Error>> {
+        for path in ["./resources/test.ini", "./resources/test2.ini"] {
+            let bytes = fs::read(path)?;
+            assert_eq!(
+                SkinDocument::from_bytes(&bytes).to_bytes(),
+                bytes,
+                "{}",
+                path
+            );
+        }
+        for entry in fs::read_dir("./resources/encoding")? {
+            let bytes = fs::read(entry?.path())?;
+            assert_eq!(SkinDocument::from_bytes(&bytes).to_bytes(), bytes);
+        }
+        let content = "\u{feff}[General]\r\nName: a\rAuthor: b\n\n// end";
+        assert_eq!(SkinDocument::load_from_str(content).to_string(), content);
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_mania_value() -> Result<(), Box<dyn Error>> {
+        let original = fs::read_to_string("./resources/test2.ini")?;
+        let mut document = SkinDocument::load_from_str(&original);
+
+        let index = document.find_mania(7).unwrap();
+        assert_eq!(document.get_mania(index, "HitPosition"), Some("400"));
+        assert!(document.set_mania(index, "HitPosition", "420")?);
+
+        let edited = document.to_string();
+        let changed: Vec<(&str, &str)> = original
+            .lines()
+            .zip(edited.lines())
+            .filter(|(before, after)| before != after)
+            .collect();
+        assert_eq!(changed, vec![("  HitPosition: 400", "  HitPosition: 420")]);
+        assert_eq!(original.lines().count(), edited.lines().count());
+        assert_eq!(document.get_mania(index - 1, "HitPosition"), Some("400"));
+        assert!(!document.set_mania(document.mania_count(), "Keys", "9")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_keeps_layout() -> Result<(), SkinError> {
+        let mut document = SkinDocument::load_from_str(
+            "// header\r\n[General]\r\n    Name :Aggro // v1\r\n\r\n[Colours]\r\nCombo1: 1,2,3",
+        );
+        document.set("General", "Name", "Other")?;
+        document.set("General", "Author", "virtual")?;
+        document.set("Colours", "Combo2", "4,5,6")?;
+        assert_eq!(
+            document.to_string(),
+            "// header\r\n[General]\r\n    Name :Other // v1\r\n    Author :virtual\r\n\r\n\
+             [Colours]\r\nCombo1: 1,2,3\r\nCombo2: 4,5,6"
+        );
+
+        document.set("Fonts", "ScorePrefix", "score")?;
+        assert!(document
+            .to_string()
+            .ends_with("Combo2: 4,5,6\r\n\r\n[Fonts]\r\nScorePrefix: score"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_rejects_multiline_values() -> Result<(), SkinError> {
+        let content = "[General]\nName: Aggro\n[Mania]\nKeys: 4\n";
+        let mut document = SkinDocument::load_from_str(content);
+        for value in ["a\nVersion: latest", "a\rb", "a // b"] {
+            assert!(matches!(
+                document.set("General", "Name", value),
+                Err(SkinError::InvalidValue { key, .. }) if key == "Name"
+            ));
+            assert!(document.set_mania(0, "NoteImage0", value).is_err());
+        }
+        assert_eq!(document.to_string(), content);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_bytes_falls_back_to_utf8() -> Result<(), SkinError> {
+        let mut document = SkinDocument::from_bytes(b"[General]\nName: caf\xE9\n");
+        assert_eq!(document.encoding(), TextEncoding::Windows1252);
+        document.set("General", "Author", "ゆげん")?;
+        assert_eq!(
+            document.to_bytes(),
+            "\u{feff}[General]\nName: café\nAuthor: ゆげん\n".as_bytes()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_like_osu() {
+        let mut document = SkinDocument::load_from_str(
+            "Name: implicit\n[General]\nVersion\n[Mania]\nKeys: 4\n[General]\nName: last\n\
+             [Mania]\nKeys: 4\nHitPosition: 420\n",
+        );
+        assert_eq!(document.get("General", "Name"), Some("last"));
+        assert_eq!(document.get("General", "Version"), Some(""));
+        assert_eq!(document.get("Mania", "HitPosition"), None);
+        assert_eq!(document.mania_count(), 2);
+        // The last block for a key count wins
+        assert_eq!(document.find_mania(4), Some(1));
+        assert_eq!(document.find_mania(7), None);
+
+        document.set("General", "Version", "2.7").unwrap();
+        assert_eq!(document.get("General", "Version"), Some("2.7"));
+        assert!(document.remove("General", "Name"));
+        assert!(!document.remove("General", "Name"));
+        assert_eq!(
+            document.to_string(),
+            "[General]\nVersion: 2.7\n[Mania]\nKeys: 4\n[General]\n\
+             [Mania]\nKeys: 4\nHitPosition: 420\n"
+        );
+    }
+
+    #[test]
+    fn test_to_skin() -> Result<(), Box<dyn Error>> {
+        let mut document = SkinDocument::load("./resources/test.ini")?;
+        let index = document.find_mania(4).unwrap();
+        document.set_mania(index, "HitPosition", "420")?;
+        document.set("General", "Name", "Edited")?;
+
+        let skin = document.to_skin()?;
+        assert_eq!(skin.general.metadata.name, "Edited");
+        assert_eq!(skin.mania[0].positions.hit_position, 420);
+        Ok(())
+    }
+}
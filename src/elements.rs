@@ -0,0 +1,337 @@
+use std::collections::BTreeSet;
+
+use crate::mania::structs::Images;
+use crate::parser::normalize_asset_path;
+use crate::resolver::LOOKUP_EXTENSIONS;
+use crate::skin::Skin;
+
+/// Start of the names of the images osu! loads from the root of a skin folder.
+///
+/// Families are matched by prefix (`ranking-`, `selection-mod-`, `taiko-`, ...) so that
+/// every variant and animation frame of an element is kept.
+pub const IMAGE_PREFIXES: &[&str] = &[
+    "approachcircle",
+    "arrow-",
+    "button-",
+    "comboburst",
+    "count",
+    "cursor",
+    "default-",
+    "fail-background",
+    "followpoint",
+    "fruit-",
+    "go",
+    "hit",
+    "inputoverlay-",
+    "lighting",
+    "mania-",
+    "masking-border",
+    "menu-",
+    "mode-",
+    "multi-skipped",
+    "options-",
+    "particle",
+    "pause-",
+    "pippidon",
+    "play-",
+    "ranking-",
+    "ready",
+    "reversearrow",
+    "score-",
+    "scorebar-",
+    "scoreentry-",
+    "section-",
+    "selection-",
+    "slider",
+    "spinner",
+    "star",
+    "taiko",
+    "target",
+    "welcome_text",
+];
+
+/// Images osu! loads for osu!standard, without extension.
+pub const STANDARD_ELEMENTS: &[&str] = &[
+    "approachcircle",
+    "cursor",
+    "cursortrail",
+    "followpoint",
+    "hit0",
+    "hit100",
+    "hit100k",
+    "hit300",
+    "hit300g",
+    "hit300k",
+    "hit50",
+    "hitcircle",
+    "hitcircleoverlay",
+    "lighting",
+    "reversearrow",
+    "sliderb0",
+    "sliderendcircle",
+    "sliderfollowcircle",
+    "sliderscorepoint",
+    "sliderstartcircle",
+    "spinner-approachcircle",
+    "spinner-background",
+    "spinner-circle",
+    "spinner-clear",
+    "spinner-metre",
+    "spinner-osu",
+    "spinner-spin",
+];
+
+/// Images osu! loads for osu!taiko, without extension.
+pub const TAIKO_ELEMENTS: &[&str] = &[
+    "taiko-bar-left",
+    "taiko-bar-right",
+    "taiko-bar-right-glow",
+    "taiko-barline",
+    "taiko-drum-inner",
+    "taiko-drum-outer",
+    "taiko-flower-group",
+    "taiko-glow",
+    "taiko-hit0",
+    "taiko-hit100",
+    "taiko-hit100k",
+    "taiko-hit300",
+    "taiko-hit300g",
+    "taiko-hit300k",
+    "taiko-roll-end",
+    "taiko-roll-middle",
+    "taiko-slider",
+    "taiko-slider-fail",
+    "taikobigcircle",
+    "taikobigcircleoverlay",
+    "taikohitcircle",
+    "taikohitcircleoverlay",
+];
+
+/// Images osu! loads for osu!catch, without extension.
+pub const CATCH_ELEMENTS: &[&str] = &[
+    "fruit-apple",
+    "fruit-apple-overlay",
+    "fruit-bananas",
+    "fruit-bananas-overlay",
+    "fruit-catcher-fail",
+    "fruit-catcher-idle",
+    "fruit-catcher-kiai",
+    "fruit-drop",
+    "fruit-drop-overlay",
+    "fruit-grapes",
+    "fruit-grapes-overlay",
+    "fruit-orange",
+    "fruit-orange-overlay",
+    "fruit-pear",
+    "fruit-pear-overlay",
+];
+
+/// Start of the names of the sounds osu! loads from the root of a skin folder.
+pub const SOUND_PREFIXES: &[&str] = &[
+    "applause",
+    "back-button-",
+    "check-",
+    "click-",
+    "combobreak",
+    "comboburst",
+    "count",
+    "drum-",
+    "failsound",
+    "gos",
+    "heartbeat",
+    "key-",
+    "match-",
+    "menu",
+    "metronomelow",
+    "multi-skipped",
+    "nightcore-",
+    "normal-",
+    "pause-",
+    "readys",
+    "sectionfail",
+    "sectionpass",
+    "seeya",
+    "select-",
+    "shutter",
+    "sliderbar",
+    "soft-",
+    "spinnerbonus",
+    "spinnerspin",
+    "taiko-",
+    "welcome",
+    "whoosh",
+];
+
+/// Extensions of the images osu! loads, the same it tries when resolving a name.
+pub const IMAGE_EXTENSIONS: &[&str] = LOOKUP_EXTENSIONS;
+/// Extensions of the sounds osu! loads.
+pub const SOUND_EXTENSIONS: &[&str] = &["wav", "mp3", "ogg"];
+
+/// Names written in skin.ini that point at files: mania images and font prefixes.
+///
+/// Names are lowercased, `/`-separated and without extension.
+pub fn referenced_names(skin: &Skin) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    let mut add = |name: &str| {
+        let name = normalize_asset_path(name).to_lowercase();
+        if !name.is_empty() {
+            names.insert(name);
+        }
+    };
+
+    for font in [&skin.fonts.hit_circle, &skin.fonts.score, &skin.fonts.combo] {
+        for glyph in font.glyph_names() {
+            add(&glyph);
+        }
+    }
+    for config in &skin.mania {
+        image_names(&config.images)
+            .iter()
+            .for_each(|name| add(name));
+    }
+    names
+}
+
+/// Every image `skin` makes osu! load: the standard, taiko and catch elements, and the
+/// images of each `[Mania]` block with the default skin's names for the ones it leaves
+/// out. Names are as written, `/`-separated and without extension.
+pub fn element_names(skin: &Skin) -> BTreeSet<String> {
+    let mut names: BTreeSet<String> = [STANDARD_ELEMENTS, TAIKO_ELEMENTS, CATCH_ELEMENTS]
+        .concat()
+        .into_iter()
+        .map(String::from)
+        .collect();
+    for config in &skin.mania {
+        names.extend(image_names(&config.effective_images()));
+    }
+    names.retain(|name| !name.is_empty());
+    names
+}
+
+/// Every name of a `[Mania]` block's images, normalized, per-column ones first.
+fn image_names(images: &Images) -> Vec<String> {
+    let stage = &images.stage;
+    let hits = &images.hits;
+    let columns = [
+        &images.keys.normal,
+        &images.keys.pressed,
+        &images.notes.regular,
+        &images.notes.hold_head,
+        &images.notes.hold_body,
+        &images.notes.hold_tail,
+    ];
+    let fixed = [
+        &stage.left,
+        &stage.right,
+        &stage.bottom,
+        &stage.hint,
+        &stage.light,
+        &stage.lighting_n,
+        &stage.lighting_l,
+        &stage.warning_arrow,
+        &hits.hit_0,
+        &hits.hit_50,
+        &hits.hit_100,
+        &hits.hit_200,
+        &hits.hit_300,
+        &hits.hit_300g,
+    ];
+    columns
+        .into_iter()
+        .flatten()
+        .chain(fixed)
+        .map(|name| normalize_asset_path(name))
+        .collect()
+}
+
+/// Whether osu! may load the file at `path` (relative to the skin folder), given the
+/// names from `referenced_names`.
+///
+/// `@2x` variants and animation frames (`name-0`, `name0`) count as their element.
+pub fn is_referenced(referenced: &BTreeSet<String>, path: &str) -> bool {
+    let path = normalize_asset_path(path).to_lowercase();
+    let Some((stem, extension)) = path.rsplit_once('.') else {
+        return false;
+    };
+    let is_image = IMAGE_EXTENSIONS.contains(&extension);
+    let is_sound = SOUND_EXTENSIONS.contains(&extension);
+    if !is_image && !is_sound {
+        return false;
+    }
+
+    let stem = stem.strip_suffix("@2x").unwrap_or(stem);
+    let without_digits = stem.trim_end_matches(|c: char| c.is_ascii_digit());
+    let without_frame = without_digits.strip_suffix('-').unwrap_or(without_digits);
+    if [stem, without_digits, without_frame]
+        .iter()
+        .any(|name| referenced.contains(*name))
+    {
+        return true;
+    }
+
+    // Fixed elements are only looked up at the root of the skin
+    if stem.contains('/') {
+        return false;
+    }
+    let prefixes = if is_image {
+        IMAGE_PREFIXES
+    } else {
+        SOUND_PREFIXES
+    };
+    prefixes.iter().any(|prefix| stem.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_referenced_names() -> Result<(), crate::SkinError> {
+        let skin = Skin::load("./resources/test.ini")?;
+        let names = referenced_names(&skin);
+        assert!(names.contains("arrownote/holdbody"));
+        assert!(names.contains("score-percent"));
+        assert!(names.contains("default-0"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_element_names() -> Result<(), crate::SkinError> {
+        let skin = Skin::load("./resources/test.ini")?;
+        let names = element_names(&skin);
+        assert!(names.contains("Arrownote/holdbody"));
+        assert!(names.contains("hitcircle"));
+        assert!(names.contains("fruit-apple"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_referenced() {
+        let names = BTreeSet::from(["arrownote/holdbody".to_string()]);
+        for path in [
+            "Arrownote\\HoldBody.png",
+            "arrownote/holdbody@2x.png",
+            "arrownote/holdbody-3.png",
+            "hitcircle@2x.png",
+            "mania-key1D.jpg",
+            "ranking-XH-small.png",
+            "sliderb12.png",
+            "normal-hitclap2.wav",
+            "combobreak.mp3",
+        ] {
+            assert!(is_referenced(&names, path), "{}", path);
+        }
+        for path in [
+            "readme.txt",
+            "hitcircle.psd",
+            "unused/hitcircle.png",
+            "arrownote/other.png",
+            "preview.png",
+            "normal-hitclap.png.bak",
+            // osu! never loads .jpeg, see `Skin::missing_assets`
+            "arrownote/holdbody.jpeg",
+        ] {
+            assert!(!is_referenced(&names, path), "{}", path);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt;
+
+use encoding_rs::{Encoding, SHIFT_JIS, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+
+/// Text encoding a skin.ini was written in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    /// UTF-8 starting with a byte order mark, as written by Notepad.
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    /// Legacy Japanese code page, common in older skins.
+    ShiftJis,
+    /// Legacy Western code page, used when nothing else fits.
+    Windows1252,
+}
+
+impl TextEncoding {
+    /// Guesses the encoding of `bytes`.
+    ///
+    /// A byte order mark wins. Without one, text with a zero byte in every other position
+    /// is UTF-16, valid UTF-8 is UTF-8, and the rest is Shift-JIS if it decodes cleanly
+    /// to Japanese text, Windows-1252 otherwise.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(b"\xEF\xBB\xBF") {
+            return TextEncoding::Utf8Bom;
+        }
+        if bytes.starts_with(b"\xFF\xFE") {
+            return TextEncoding::Utf16Le;
+        }
+        if bytes.starts_with(b"\xFE\xFF") {
+            return TextEncoding::Utf16Be;
+        }
+        if let Some(encoding) = detect_utf16(bytes) {
+            return encoding;
+        }
+        if std::str::from_utf8(bytes).is_ok() {
+            return TextEncoding::Utf8;
+        }
+        match SHIFT_JIS.decode_without_bom_handling_and_without_replacement(bytes) {
+            Some(text) if text.chars().any(is_japanese) => TextEncoding::ShiftJis,
+            _ => TextEncoding::Windows1252,
+        }
+    }
+
+    /// Decodes `bytes`, dropping the byte order mark. Invalid sequences become U+FFFD.
+    pub fn decode(self, bytes: &[u8]) -> String {
+        let bytes = match self {
+            TextEncoding::Utf8Bom => bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes),
+            TextEncoding::Utf16Le => bytes.strip_prefix(b"\xFF\xFE").unwrap_or(bytes),
+            TextEncoding::Utf16Be => bytes.strip_prefix(b"\xFE\xFF").unwrap_or(bytes),
+            _ => bytes,
+        };
+        let (text, _) = self.encoding().decode_without_bom_handling(bytes);
+        text.into_owned()
+    }
+
+    /// Encodes `text` without byte order mark.
+    ///
+    /// `None` when `text` has characters a legacy code page lacks.
+    pub fn encode(self, text: &str) -> Option<Vec<u8>> {
+        match self {
+            TextEncoding::Utf16Le => Some(text.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+            TextEncoding::Utf16Be => Some(text.encode_utf16().flat_map(u16::to_be_bytes).collect()),
+            // encoding_rs never encodes to UTF-16, so it is done by hand
+            encoding => {
+                let (bytes, _, unmappable) = encoding.encoding().encode(text);
+                (!unmappable).then(|| bytes.into_owned())
+            }
+        }
+    }
+
+    /// The byte order mark written before text in this encoding, empty for `Utf8` and the
+    /// code pages.
+    pub fn byte_order_mark(self) -> &'static [u8] {
+        match self {
+            TextEncoding::Utf8Bom => b"\xEF\xBB\xBF",
+            TextEncoding::Utf16Le => b"\xFF\xFE",
+            TextEncoding::Utf16Be => b"\xFE\xFF",
+            TextEncoding::Utf8 | TextEncoding::ShiftJis | TextEncoding::Windows1252 => b"",
+        }
+    }
+
+    fn encoding(self) -> &'static Encoding {
+        match self {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => UTF_8,
+            TextEncoding::Utf16Le => UTF_16LE,
+            TextEncoding::Utf16Be => UTF_16BE,
+            TextEncoding::ShiftJis => SHIFT_JIS,
+            TextEncoding::Windows1252 => WINDOWS_1252,
+        }
+    }
+}
+
+impl fmt::Display for TextEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf8Bom => "UTF-8 with BOM",
+            TextEncoding::Utf16Le => "UTF-16LE",
+            TextEncoding::Utf16Be => "UTF-16BE",
+            TextEncoding::ShiftJis => "Shift_JIS",
+            TextEncoding::Windows1252 => "windows-1252",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Detects and decodes `bytes`.
+pub fn decode(bytes: &[u8]) -> (String, TextEncoding) {
+    let encoding = TextEncoding::detect(bytes);
+    (encoding.decode(bytes), encoding)
+}
+
+/// UTF-16 without BOM: a skin.ini is mostly ASCII, so half of its bytes are zero.
+fn detect_utf16(bytes: &[u8]) -> Option<TextEncoding> {
+    if bytes.len() < 2 || !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let zeros_at = |parity: usize| {
+        bytes
+            .iter()
+            .skip(parity)
+            .step_by(2)
+            .filter(|&&b| b == 0)
+            .count()
+    };
+    let pairs = bytes.len() / 2;
+    // ASCII text in UTF-16LE has its zeros at odd positions
+    if zeros_at(1) * 2 > pairs && zeros_at(0) == 0 {
+        Some(TextEncoding::Utf16Le)
+    } else if zeros_at(0) * 2 > pairs && zeros_at(1) == 0 {
+        Some(TextEncoding::Utf16Be)
+    } else {
+        None
+    }
+}
+
+/// Kana, CJK ideographs and full-width forms.
+fn is_japanese(c: char) -> bool {
+    matches!(c, '\u{3040}'..='\u{30FF}' | '\u{4E00}'..='\u{9FFF}' | '\u{FF00}'..='\u{FFEF}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> Vec<u8> {
+        std::fs::read(format!("./resources/encoding/{}", name)).unwrap()
+    }
+
+    #[test]
+    fn test_detect_fixtures() {
+        let cases = [
+            ("utf8.ini", TextEncoding::Utf8, "Name: Café ゆげん"),
+            ("utf8_bom.ini", TextEncoding::Utf8Bom, "Name: Café ゆげん"),
+            ("utf16le.ini", TextEncoding::Utf16Le, "Name: Café ゆげん"),
+            ("utf16be.ini", TextEncoding::Utf16Be, "Name: Café ゆげん"),
+            (
+                "utf16le_no_bom.ini",
+                TextEncoding::Utf16Le,
+                "Name: Café ゆげん",
+            ),
+            ("shift_jis.ini", TextEncoding::ShiftJis, "Name: ゆげん"),
+            ("windows1252.ini", TextEncoding::Windows1252, "Name: Café"),
+        ];
+        for (name, expected, line) in cases {
+            let (text, encoding) = decode(&fixture(name));
+            assert_eq!(encoding, expected, "{}", name);
+            assert!(text.starts_with("[General]"), "{}: {:?}", name, text);
+            assert_eq!(text.lines().nth(1), Some(line), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_encode_fixtures() {
+        for name in [
+            "utf8.ini",
+            "utf8_bom.ini",
+            "utf16le.ini",
+            "utf16be.ini",
+            "utf16le_no_bom.ini",
+            "shift_jis.ini",
+            "windows1252.ini",
+        ] {
+            let bytes = fixture(name);
+            let (text, encoding) = decode(&bytes);
+            let bom = encoding.byte_order_mark();
+            let body = match bytes.strip_prefix(bom) {
+                Some(body) if !bom.is_empty() => body,
+                _ => &bytes[..],
+            };
+            assert_eq!(encoding.encode(&text).as_deref(), Some(body), "{}", name);
+        }
+        assert_eq!(TextEncoding::Windows1252.encode("ゆ"), None);
+        assert_eq!(TextEncoding::ShiftJis.encode("Café"), None);
+        assert!(TextEncoding::Utf8.byte_order_mark().is_empty());
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(TextEncoding::detect(b""), TextEncoding::Utf8);
+        assert_eq!(TextEncoding::detect(b"Name: x"), TextEncoding::Utf8);
+        // "é" in windows-1252 is not valid UTF-8, nor a valid Shift-JIS sequence here
+        assert_eq!(
+            TextEncoding::detect(b"Name: caf\xE9\n"),
+            TextEncoding::Windows1252
+        );
+        assert_eq!(TextEncoding::Utf16Le.to_string(), "UTF-16LE");
+    }
+}
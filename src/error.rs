@@ -0,0 +1,146 @@
+use std::fmt;
+
+/// Everything that can go wrong while reading a skin.ini.
+///
+/// `line` is 1-based and refers to the original file.
+#[derive(Debug)]
+pub enum SkinError {
+    Io(std::io::Error),
+    /// A line osu! cannot split into a section header or a `Key: Value` pair.
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// A `.osk` that is not a readable zip archive.
+    Archive(zip::result::ZipError),
+    /// A `.osk` entry, or the whole archive when `entry` is `None`, larger than `limit`
+    /// bytes once decompressed.
+    ArchiveTooLarge {
+        entry: Option<String>,
+        limit: u64,
+    },
+    /// A known key whose value cannot be used, e.g. `HitPosition: abc`.
+    InvalidValue {
+        section: String,
+        key: String,
+        value: String,
+        line: Option<usize>,
+    },
+    /// A key written more than once in the same section.
+    DuplicateKey {
+        section: String,
+        key: String,
+        value: String,
+        line: Option<usize>,
+    },
+    /// A key osu! does not read in this section.
+    UnknownKey {
+        section: String,
+        key: String,
+        value: String,
+        line: Option<usize>,
+    },
+    /// A per-column key pointing past the last column, e.g. `KeyImage5` with `Keys: 4`.
+    ColumnOutOfRange {
+        section: String,
+        key: String,
+        value: String,
+        column: u32,
+        key_count: u32,
+        line: Option<usize>,
+    },
+}
+
+impl SkinError {
+    /// Line of the offending entry, when it is known.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            SkinError::Io(_) | SkinError::Archive(_) | SkinError::ArchiveTooLarge { .. } => None,
+            SkinError::Syntax { line, .. } => Some(*line),
+            SkinError::InvalidValue { line, .. }
+            | SkinError::DuplicateKey { line, .. }
+            | SkinError::UnknownKey { line, .. }
+            | SkinError::ColumnOutOfRange { line, .. } => *line,
+        }
+    }
+}
+
+impl fmt::Display for SkinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkinError::Io(err) => write!(f, "could not read skin.ini: {}", err),
+            SkinError::Archive(err) => write!(f, "could not read skin archive: {}", err),
+            SkinError::Syntax {
+                line,
+                column,
+                message,
+            } => write!(f, "syntax error at {}:{}: {}", line, column, message),
+            SkinError::ArchiveTooLarge {
+                entry: Some(entry),
+                limit,
+            } => write!(
+                f,
+                "skin archive entry \"{}\" is larger than {} bytes",
+                entry, limit
+            ),
+            SkinError::ArchiveTooLarge { entry: None, limit } => {
+                write!(f, "skin archive is larger than {} bytes", limit)
+            }
+            SkinError::InvalidValue {
+                section,
+                key,
+                value,
+                ..
+            } => write!(f, "[{}] {}: invalid value \"{}\"", section, key, value),
+            SkinError::DuplicateKey { section, key, .. } => {
+                write!(f, "[{}] {}: duplicate key", section, key)
+            }
+            SkinError::UnknownKey { section, key, .. } => {
+                write!(f, "[{}] {}: unknown key", section, key)
+            }
+            SkinError::ColumnOutOfRange {
+                section,
+                key,
+                column,
+                key_count,
+                ..
+            } => write!(
+                f,
+                "[{}] {}: column {} is out of range for {} keys",
+                section, key, column, key_count
+            ),
+        }?;
+        match self.line() {
+            Some(line) if !matches!(self, SkinError::Syntax { .. }) => {
+                write!(f, " (line {})", line)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for SkinError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SkinError::Io(err) => Some(err),
+            SkinError::Archive(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SkinError {
+    fn from(err: std::io::Error) -> Self {
+        SkinError::Io(err)
+    }
+}
+
+impl From<zip::result::ZipError> for SkinError {
+    fn from(err: zip::result::ZipError) -> Self {
+        match err {
+            zip::result::ZipError::Io(err) => SkinError::Io(err),
+            err => SkinError::Archive(err),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use crate::animation::{Animation, FrameRate};
+use crate::elements::element_names;
+use crate::resolver::{AssetResolver, ResolveOptions, ResolvedAsset};
+use crate::skin::Skin;
+
+/// Files of osu!'s default skin, one per line.
+const DEFAULT_SKIN_MANIFEST: &str = include_str!("../resources/default-skin.txt");
+
+/// Where a file of a `FallbackResolver` comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SourceKind {
+    /// The skin being resolved.
+    Skin,
+    /// The skin chosen to fill in its gaps, see `FallbackResolver::with_base`.
+    Base,
+    /// osu!'s default skin.
+    Default,
+}
+
+/// A file found by a `FallbackResolver`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourcedAsset {
+    /// The source the file was found in.
+    pub source: SourceKind,
+    /// The file itself, with its path in that source.
+    pub asset: ResolvedAsset,
+}
+
+/// Which source supplies an element, `None` when none has it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElementSource {
+    /// The element name as asked for, without extension.
+    pub name: String,
+    /// Its first frame or static image, from the first source that has it.
+    pub resolved: Option<SourcedAsset>,
+}
+
+/// Looks for files in a skin, then in an optional base skin, then in osu!'s default skin,
+/// like the game does when a skin leaves an element out.
+#[derive(Clone, Debug)]
+pub struct FallbackResolver {
+    /// In lookup order.
+    sources: Vec<(SourceKind, AssetResolver)>,
+}
+
+impl FallbackResolver {
+    /// Falls back from `skin` to the default skin.
+    pub fn new(skin: AssetResolver) -> Self {
+        Self {
+            sources: vec![
+                (SourceKind::Skin, skin),
+                (SourceKind::Default, default_skin()),
+            ],
+        }
+    }
+
+    /// Looks in `base` after the skin and before the default skin, replacing any previous
+    /// base skin.
+    pub fn with_base(mut self, base: AssetResolver) -> Self {
+        self.sources.retain(|(kind, _)| *kind != SourceKind::Base);
+        self.sources.insert(1, (SourceKind::Base, base));
+        self
+    }
+
+    /// The sources, in lookup order.
+    pub fn sources(&self) -> impl Iterator<Item = (SourceKind, &AssetResolver)> {
+        self.sources
+            .iter()
+            .map(|(kind, resolver)| (*kind, resolver))
+    }
+
+    /// The file osu! loads for `name` from the first source that has one.
+    pub fn resolve(&self, name: &str, options: ResolveOptions) -> Option<SourcedAsset> {
+        self.sources.iter().find_map(|(source, resolver)| {
+            let asset = resolver.resolve(name, options)?;
+            Some(SourcedAsset {
+                source: *source,
+                asset,
+            })
+        })
+    }
+
+    /// The frames of `name` from the first source that has the element, animated or not.
+    /// osu! never mixes frames of different sources.
+    pub fn animation(
+        &self,
+        name: &str,
+        rate: FrameRate,
+        options: ResolveOptions,
+    ) -> Option<(SourceKind, Animation)> {
+        self.sources.iter().find_map(|(source, resolver)| {
+            Some((*source, resolver.animation(name, rate, options)?))
+        })
+    }
+
+    /// Which source supplies each of `names`, in the same order.
+    pub fn report<S: AsRef<str>>(
+        &self,
+        names: impl IntoIterator<Item = S>,
+        options: ResolveOptions,
+    ) -> Vec<ElementSource> {
+        names
+            .into_iter()
+            .map(|name| {
+                let name = name.as_ref();
+                let resolved = self.animation(name, FrameRate::OneSecond, options).map(
+                    |(source, animation)| SourcedAsset {
+                        source,
+                        asset: animation.frames[0].clone(),
+                    },
+                );
+                ElementSource {
+                    name: name.to_string(),
+                    resolved,
+                }
+            })
+            .collect()
+    }
+
+    /// `report` for every element `skin` loads, see `elements::element_names`.
+    pub fn report_skin(&self, skin: &Skin, options: ResolveOptions) -> Vec<ElementSource> {
+        self.report(element_names(skin), options)
+    }
+}
+
+/// A resolver over the files of osu!'s default skin. Only names are known, not content.
+pub fn default_skin() -> AssetResolver {
+    AssetResolver::from_paths(
+        DEFAULT_SKIN_MANIFEST
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source_of(report: &[ElementSource], name: &str) -> Option<SourceKind> {
+        report
+            .iter()
+            .find(|element| element.name == name)
+            .unwrap()
+            .resolved
+            .as_ref()
+            .map(|resolved| resolved.source)
+    }
+
+    #[test]
+    fn test_fallback_order() {
+        let skin = AssetResolver::from_paths(["mania-hit0.png", "hitcircle-0.png"]);
+        let base = AssetResolver::from_paths(["mania-hit0.png", "mania-hit50.png"]);
+        let resolver = FallbackResolver::new(skin).with_base(base);
+        let options = ResolveOptions::default();
+
+        let hit0 = resolver.resolve("mania-hit0", options).unwrap();
+        assert_eq!(hit0.source, SourceKind::Skin);
+        let hit50 = resolver.resolve("mania-hit50", options).unwrap();
+        assert_eq!(hit50.source, SourceKind::Base);
+        let hit300g = resolver.resolve("mania-hit300g", options).unwrap();
+        assert_eq!(hit300g.source, SourceKind::Default);
+        assert_eq!(hit300g.asset.path, "mania-hit300g.png");
+        assert!(resolver.resolve("Arrownote/holdbody", options).is_none());
+
+        let report = resolver.report(["hitcircle", "mania-hit50", "unknown"], options);
+        assert_eq!(source_of(&report, "hitcircle"), Some(SourceKind::Skin));
+        assert_eq!(source_of(&report, "mania-hit50"), Some(SourceKind::Base));
+        assert_eq!(source_of(&report, "unknown"), None);
+    }
+
+    #[test]
+    fn test_report_skin() -> Result<(), crate::SkinError> {
+        let skin = Skin::load("./resources/test.ini")?;
+        let resolver = FallbackResolver::new(AssetResolver::from_paths([
+            "Arrownote/HoldBody.png",
+            "taikohitcircle.png",
+        ]));
+        let report = resolver.report_skin(&skin, ResolveOptions::default());
+
+        assert_eq!(
+            source_of(&report, "Arrownote/holdbody"),
+            Some(SourceKind::Skin)
+        );
+        assert_eq!(source_of(&report, "taikohitcircle"), Some(SourceKind::Skin));
+        assert_eq!(
+            source_of(&report, "approachcircle"),
+            Some(SourceKind::Default)
+        );
+        // Custom names only exist in the skin
+        assert_eq!(source_of(&report, "Arrownote/holdcap"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_skin_has_every_default_element() {
+        let default = default_skin();
+        let options = ResolveOptions::default();
+        for name in crate::elements::STANDARD_ELEMENTS
+            .iter()
+            .chain(crate::elements::TAIKO_ELEMENTS)
+            .chain(crate::elements::CATCH_ELEMENTS)
+        {
+            assert!(default.resolve(name, options).is_some(), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use crate::diagnostic::{Diagnostic, DiagnosticKind, Diagnostics};
+use crate::error::SkinError;
+use crate::lexer::{strip_comment, tokenize, Token, TokenKind};
+use crate::source::Span;
+
+/// Sections that start a new block each time they appear instead of continuing the
+/// previous one: every `[Mania]` describes its own key count.
+pub(crate) const REPEATED_SECTIONS: &[&str] = &["Mania"];
+
+/// The `Key: Value` entries of a section, in file order and with duplicates.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Properties {
+    entries: Vec<(String, String)>,
+}
+
+impl Properties {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets `key` to `value`, replacing every previous value.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        let key = key.into();
+        self.entries.retain(|(k, _)| *k != key);
+        self.entries.push((key, value.into()));
+    }
+
+    /// Adds `value` after the existing values of `key`.
+    pub fn append(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.entries.push((key.into(), value.into()));
+    }
+
+    /// First value of `key`. osu! uses the last one, see `get_last`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Last value of `key`, the one osu! keeps.
+    pub fn get_last(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .rfind(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> {
+        self.entries
+            .iter()
+            .filter(move |(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.entries.iter().any(|(k, _)| k == key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// A skin.ini split into sections.
+///
+/// Follows osu! rather than the INI format: keys written before the first header belong
+/// to `[General]`, and a section written twice is read as one, except `[Mania]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Ini {
+    sections: Vec<(String, Properties)>,
+}
+
+impl Ini {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses `content`. Like osu!, this never fails: lines it cannot make sense of
+    /// become keys that the section parsers do not know.
+    pub fn load_from_str(content: &str) -> Self {
+        let sections = group_sections(&tokenize(content))
+            .into_iter()
+            .map(|group| {
+                let mut props = Properties::new();
+                for (key, value, _) in group.entries {
+                    props.append(key, value);
+                }
+                (group.name.to_string(), props)
+            })
+            .collect();
+        Self { sections }
+    }
+
+    /// The section called `name`, or the first one for `[Mania]`.
+    pub fn section(&self, name: &str) -> Option<&Properties> {
+        self.sections
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, props)| props)
+    }
+
+    /// Every section called `name`, in file order.
+    pub fn section_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Properties> {
+        self.sections
+            .iter()
+            .filter(move |(n, _)| n == name)
+            .map(|(_, props)| props)
+    }
+
+    pub fn sections(&self) -> impl Iterator<Item = (&str, &Properties)> {
+        self.sections
+            .iter()
+            .map(|(name, props)| (name.as_str(), props))
+    }
+}
+
+/// Entries of one section as osu! sees it, possibly gathered from several headers.
+pub(crate) struct SectionGroup<'a> {
+    pub name: &'a str,
+    /// First header of the section, `None` when it is never written and only holds keys
+    /// from before the first header.
+    pub header: Option<Span>,
+    pub entries: Vec<(&'a str, &'a str, Span)>,
+}
+
+/// Assigns every entry to its section, shared by `Ini` and `SourceMap` so that both
+/// agree on which block a key belongs to.
+pub(crate) fn group_sections<'a>(tokens: &[Token<'a>]) -> Vec<SectionGroup<'a>> {
+    let mut groups: Vec<SectionGroup<'a>> = Vec::new();
+    let mut current = None;
+
+    for token in tokens {
+        match token.kind {
+            TokenKind::Section(name) => {
+                let existing = groups.iter().position(|group| group.name == name);
+                current = Some(match existing {
+                    Some(index) if !REPEATED_SECTIONS.contains(&name) => {
+                        let group = &mut groups[index];
+                        group.header.get_or_insert(token.span);
+                        index
+                    }
+                    _ => {
+                        groups.push(SectionGroup {
+                            name,
+                            header: Some(token.span),
+                            entries: Vec::new(),
+                        });
+                        groups.len() - 1
+                    }
+                });
+            }
+            TokenKind::Entry { key, value } => {
+                let index = *current.get_or_insert_with(|| {
+                    // osu! starts reading in [General]
+                    groups.push(SectionGroup {
+                        name: "General",
+                        header: None,
+                        entries: Vec::new(),
+                    });
+                    groups.len() - 1
+                });
+                groups[index].entries.push((key, value, token.span));
+            }
+            TokenKind::Blank | TokenKind::Comment => {}
+        }
+    }
+
+    groups
+}
+
+/// Reports the lines of `content` that osu! reads as a key although they are not
+/// `Key: Value` pairs: malformed section headers and lines without a colon.
+pub(crate) fn report_syntax(content: &str, diagnostics: &mut Diagnostics) -> Result<(), SkinError> {
+    let mut section = "General";
+    for token in tokenize(content) {
+        let key = match token.kind {
+            TokenKind::Section(name) => {
+                section = name;
+                continue;
+            }
+            TokenKind::Entry { key, .. } => key,
+            TokenKind::Blank | TokenKind::Comment => continue,
+        };
+        let column = token.span.column;
+        let kind = if key.starts_with('[') {
+            DiagnosticKind::MalformedHeader { column }
+        } else if !strip_comment(token.raw).contains(':') {
+            DiagnosticKind::MissingColon { column }
+        } else {
+            continue;
+        };
+        let mut diagnostic = Diagnostic::new(section, key, "", kind);
+        diagnostic.line = Some(token.span.line);
+        diagnostics.report(diagnostic)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_properties() {
+        let mut props = Properties::new();
+        props.append("Keys", "4");
+        props.append("Keys", "7");
+        props.insert("Name", "a");
+        assert_eq!(props.get("Keys"), Some("4"));
+        assert_eq!(props.get_last("Keys"), Some("7"));
+        assert_eq!(props.len(), 3);
+
+        props.insert("Keys", "5");
+        assert_eq!(props.get_all("Keys").collect::<Vec<_>>(), vec!["5"]);
+        assert!(props.contains_key("Name"));
+        assert!(!props.contains_key("Author"));
+    }
+
+    #[test]
+    fn test_load_from_str() {
+        let ini = Ini::load_from_str(
+            "Name: before\n[Mania]\nKeys: 4\n[General]\nAuthor: x\n[Mania]\nKeys: 7\n\
+             [General]\nName: after\n[Custom]\nFoo: bar\n",
+        );
+        let names: Vec<&str> = ini.sections().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["General", "Mania", "Mania", "Custom"]);
+
+        // Both [General] headers and the keys before them are one section
+        let general = ini.section("General").unwrap();
+        assert_eq!(
+            general.get_all("Name").collect::<Vec<_>>(),
+            vec!["before", "after"]
+        );
+        assert_eq!(general.get("Author"), Some("x"));
+
+        let keys: Vec<&str> = ini
+            .section_all("Mania")
+            .filter_map(|props| props.get("Keys"))
+            .collect();
+        assert_eq!(keys, vec!["4", "7"]);
+        assert!(ini.section("Fonts").is_none());
+    }
+
+    #[test]
+    fn test_load_compat_corpus() {
+        let content = std::fs::read_to_string("./resources/compat.ini").unwrap();
+        let ini = Ini::load_from_str(&content);
+
+        let general = ini.section("General").unwrap();
+        assert_eq!(general.get("Name"), Some("Compat"));
+        assert_eq!(general.get("Author"), Some("osu!"));
+        assert_eq!(general.get("Version"), Some("2.7"));
+        assert!(general.contains_key("; not a comment"));
+        assert!(general.contains_key("Unseparated = value"));
+
+        let fonts = ini.section("Fonts").unwrap();
+        assert_eq!(fonts.get("ScorePrefix"), Some("fonts\\score"));
+        assert_eq!(fonts.get("ComboPrefix"), Some("fonts\\combo"));
+
+        let mania: Vec<&Properties> = ini.section_all("Mania").collect();
+        assert_eq!(mania.len(), 2);
+        assert_eq!(mania[0].get("Keys"), Some("4"));
+        assert_eq!(mania[0].get("KeyImage0"), Some("mania\\key left"));
+        assert_eq!(mania[1].get("Keys"), Some("7"));
+        assert_eq!(mania[1].get_last("HitPosition"), Some("420"));
+    }
+}
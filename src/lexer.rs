@@ -0,0 +1,197 @@
+use crate::source::Span;
+
+/// What a single line of a skin.ini is, according to osu!'s parser.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind<'a> {
+    /// Empty or whitespace-only line.
+    Blank,
+    /// Line whose first non-blank characters are `//`.
+    Comment,
+    /// `[Name]`, starting at the first column.
+    Section(&'a str),
+    /// Anything else, split at the first `:`. Lines without a colon have an empty value.
+    Entry { key: &'a str, value: &'a str },
+}
+
+/// A line of the file, with the position of its header or key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind<'a>,
+    pub span: Span,
+    /// The line as written, without its line ending.
+    pub raw: &'a str,
+    /// `\n`, `\r\n`, `\r`, or empty for a last line with no line ending.
+    pub ending: &'a str,
+}
+
+/// Splits `content` into one token per line, the way osu! stable reads a skin.ini.
+///
+/// - `\n`, `\r\n` and a lone `\r` all end a line, and a leading BOM is dropped.
+/// - Lines starting with `//` are comments, and `//` anywhere after the first character
+///   cuts the rest of the line. `;` and `#` have no special meaning.
+/// - A section header must start at the first column and end with `]` once trailing
+///   whitespace is removed.
+/// - Key and value are separated by the first `:` (never `=`) and trimmed. Backslashes
+///   are kept as they are, so `receptors\left` needs no escaping.
+pub fn tokenize(content: &str) -> Vec<Token<'_>> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    lines(content)
+        .enumerate()
+        .map(|(index, (raw, ending))| Token {
+            ending,
+            ..tokenize_line(raw, index + 1)
+        })
+        .collect()
+}
+
+pub(crate) fn tokenize_line(raw: &str, line: usize) -> Token<'_> {
+    let trimmed = raw.trim_start();
+    let span = Span {
+        line,
+        column: raw.chars().count() - trimmed.chars().count() + 1,
+    };
+
+    let kind = if trimmed.is_empty() {
+        TokenKind::Blank
+    } else if trimmed.starts_with("//") {
+        TokenKind::Comment
+    } else {
+        let code = strip_comment(raw).trim_end();
+        match code
+            .strip_prefix('[')
+            .and_then(|header| header.strip_suffix(']'))
+        {
+            Some(name) => TokenKind::Section(name),
+            None => {
+                let (key, value) = code.split_once(':').unwrap_or((code, ""));
+                TokenKind::Entry {
+                    key: key.trim(),
+                    value: value.trim(),
+                }
+            }
+        }
+    };
+
+    Token {
+        kind,
+        span,
+        raw,
+        ending: "",
+    }
+}
+
+/// Cuts an inline `//` comment. osu! keeps a `//` at the very start of the line.
+pub(crate) fn strip_comment(line: &str) -> &str {
+    match line.find("//") {
+        Some(index) if index > 0 => &line[..index],
+        _ => line,
+    }
+}
+
+/// Lines of `content` and their ending, `\n`, `\r\n` or `\r` like .NET's `ReadLine`.
+fn lines(content: &str) -> impl Iterator<Item = (&str, &str)> {
+    let mut rest = Some(content);
+    std::iter::from_fn(move || {
+        let current = rest?;
+        match current.find(['\r', '\n']) {
+            Some(end) => {
+                let next = if current[end..].starts_with("\r\n") {
+                    end + 2
+                } else {
+                    end + 1
+                };
+                rest = (next < current.len()).then(|| &current[next..]);
+                Some((&current[..end], &current[end..next]))
+            }
+            None => {
+                rest = None;
+                (!current.is_empty()).then_some((current, ""))
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(content: &str) -> Vec<TokenKind<'_>> {
+        tokenize(content)
+            .into_iter()
+            .map(|token| token.kind)
+            .collect()
+    }
+
+    fn entry<'a>(key: &'a str, value: &'a str) -> TokenKind<'a> {
+        TokenKind::Entry { key, value }
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            kinds("[General]\nName: Aggro\n\n// comment\n  Author:virtual  \n"),
+            vec![
+                TokenKind::Section("General"),
+                entry("Name", "Aggro"),
+                TokenKind::Blank,
+                TokenKind::Comment,
+                entry("Author", "virtual"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_compatibility() {
+        // Each case is one line and what osu! stable makes of it
+        let cases = [
+            (
+                "KeyImage0: receptors\\left",
+                entry("KeyImage0", "receptors\\left"),
+            ),
+            ("Name: a // b", entry("Name", "a")),
+            ("Name: http://x", entry("Name", "http:")),
+            ("  // indented comment", TokenKind::Comment),
+            ("HitPosition = 420", entry("HitPosition = 420", "")),
+            ("; not a comment", entry("; not a comment", "")),
+            ("# not a comment", entry("# not a comment", "")),
+            ("Version", entry("Version", "")),
+            ("Keys: 4: 5", entry("Keys", "4: 5")),
+            ("[Mania]   ", TokenKind::Section("Mania")),
+            ("[Mania] // 4K", TokenKind::Section("Mania")),
+            ("  [Mania]", entry("[Mania]", "")),
+            ("[Mania", entry("[Mania", "")),
+            ("[]", TokenKind::Section("")),
+            ("\t", TokenKind::Blank),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(tokenize_line(line, 1).kind, expected, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn test_tokenize_line_endings() {
+        let tokens = tokenize("\u{feff}[General]\r\nName: a\rAuthor: b\n\nVersion: 2.7");
+        let lines: Vec<(usize, &str)> = tokens.iter().map(|t| (t.span.line, t.raw)).collect();
+        assert_eq!(
+            lines,
+            vec![
+                (1, "[General]"),
+                (2, "Name: a"),
+                (3, "Author: b"),
+                (4, ""),
+                (5, "Version: 2.7"),
+            ]
+        );
+        let endings: Vec<&str> = tokens.iter().map(|t| t.ending).collect();
+        assert_eq!(endings, vec!["\r\n", "\r", "\n", "\n", ""]);
+        assert!(tokenize("").is_empty());
+        assert_eq!(tokenize("a\n").len(), 1);
+    }
+
+    #[test]
+    fn test_tokenize_span() {
+        let token = tokenize_line("  ❯Name: x", 7);
+        assert_eq!(token.span, Span { line: 7, column: 3 });
+        assert_eq!(token.kind, entry("❯Name", "x"));
+    }
+}
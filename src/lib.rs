@@ -1,4 +1,35 @@
-mod structs;
-mod Mania;
-mod mania;
+mod animation;
+mod assets;
+mod diagnostic;
+mod document;
+pub mod elements;
+mod encoding;
+mod error;
+mod fallback;
+pub mod ini;
+mod lexer;
+pub mod mania;
+mod osk;
+pub mod parser;
+mod report;
+mod resolver;
+mod skin;
+mod source;
+pub mod structs;
+pub mod writer;
 
+pub use animation::{Animation, FrameRate};
+pub use assets::AssetStore;
+pub use diagnostic::{Diagnostic, DiagnosticKind, Diagnostics, ParseMode};
+pub use document::SkinDocument;
+pub use encoding::{decode, TextEncoding};
+pub use error::SkinError;
+pub use fallback::{default_skin, ElementSource, FallbackResolver, SourceKind, SourcedAsset};
+pub use lexer::{tokenize, Token, TokenKind};
+pub use osk::{write_osk, OskArchive, OskWriteOptions, MAX_ARCHIVE_SIZE, MAX_ENTRY_SIZE};
+pub use report::{
+    AssetProblem, AssetSection, MissingAsset, MissingAssets, UnusedFile, UnusedFiles,
+};
+pub use resolver::{candidates, AssetResolver, ResolveOptions, ResolvedAsset, LOOKUP_EXTENSIONS};
+pub use skin::Skin;
+pub use source::{Location, SectionSpans, SourceMap, Span};
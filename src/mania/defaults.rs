@@ -0,0 +1,427 @@
+use crate::mania::parser::MAX_KEY_COUNT;
+use crate::mania::structs::{
+    HitImages, Images, KeyImages, ManiaConfig, NoteImages, SpecialStyle, StageImages,
+};
+
+// osu! stable's values for what the skin leaves out of a key count's block
+pub const COLUMN_START: u32 = 136;
+pub const COLUMN_RIGHT: u32 = 19;
+/// `ColumnWidth` of every column, by key count from 1K: 30 as long as the stage fits
+/// between `ColumnStart` and `ColumnRight` on the 640 wide screen, narrower past 16K.
+pub const COLUMN_WIDTHS: [u32; MAX_KEY_COUNT as usize] = [
+    30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 28, 26,
+];
+pub const COLUMN_SPACING: u32 = 0;
+pub const COLUMN_LINE_WIDTH: u32 = 2;
+pub const HIT_POSITION: u32 = 402;
+pub const LIGHT_POSITION: u32 = 413;
+pub const SCORE_POSITION: u32 = 300;
+pub const COMBO_POSITION: u32 = 111;
+/// `Colour#`: columns are black.
+pub const COLUMN_COLOUR: [u8; 4] = [0, 0, 0, 255];
+/// `ColourLight#`: key lighting is left untinted.
+pub const LIGHT_COLOUR: [u8; 4] = [255, 255, 255, 255];
+
+/// The defaults of the per-column lists of a `key_count` keys block, see
+/// `key_count_defaults`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyCountDefaults {
+    /// `ColumnWidth`, one per column.
+    pub column_width: Vec<u32>,
+    /// `ColumnSpacing`, one per gap between columns.
+    pub column_spacing: Vec<u32>,
+    /// `ColumnLineWidth`, one per line around the columns.
+    pub column_line_width: Vec<u32>,
+    /// `Colour#`, one per column.
+    pub colours: Vec<[u8; 4]>,
+    /// `ColourLight#`, one per column.
+    pub light_colours: Vec<[u8; 4]>,
+}
+
+/// What osu! uses for the per-column lists of a `key_count` keys block. Past
+/// `MAX_KEY_COUNT`, columns are as wide as for the largest key count.
+pub fn key_count_defaults(key_count: u32) -> KeyCountDefaults {
+    let columns = key_count as usize;
+    let width = COLUMN_WIDTHS[columns.clamp(1, COLUMN_WIDTHS.len()) - 1];
+    KeyCountDefaults {
+        column_width: vec![width; columns],
+        column_spacing: vec![COLUMN_SPACING; columns.saturating_sub(1)],
+        column_line_width: vec![COLUMN_LINE_WIDTH; columns + 1],
+        colours: vec![COLUMN_COLOUR; columns],
+        light_colours: vec![LIGHT_COLOUR; columns],
+    }
+}
+
+/// Which default images a column uses: `mania-key1`, `mania-key2` or `mania-keyS`, and the
+/// matching `mania-note` sprites.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnType {
+    Odd,
+    Even,
+    Special,
+}
+
+impl ColumnType {
+    /// `1`, `2` or `S`, as in `mania-key1`.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            ColumnType::Odd => "1",
+            ColumnType::Even => "2",
+            ColumnType::Special => "S",
+        }
+    }
+}
+
+/// Column types of a `key_count` keys stage: the middle column of an odd stage is
+/// special, the others alternate from the edges inward (`1 2 1 S 1 2 1` for 7K).
+pub fn column_types(key_count: u32) -> Vec<ColumnType> {
+    (0..key_count)
+        .map(|column| {
+            if key_count % 2 == 1 && column == key_count / 2 {
+                return ColumnType::Special;
+            }
+            let distance_to_edge = column.min(key_count - 1 - column);
+            if distance_to_edge.is_multiple_of(2) {
+                ColumnType::Odd
+            } else {
+                ColumnType::Even
+            }
+        })
+        .collect()
+}
+
+/// Column types of a `key_count` keys layout with the skin's `SpecialStyle` and
+/// `SplitStages`.
+///
+/// Split stages lay out each half of an even layout on its own. On an even stage,
+/// style 1 makes the leftmost column special and style 2 the rightmost, the others
+/// alternating from there (`S 1 2 1 2 1 2 1` for 8K with style 1). Odd stages already
+/// have their special column in the middle and ignore the style.
+pub fn stage_column_types(key_count: u32, style: &SpecialStyle) -> Vec<ColumnType> {
+    if style.split_stages && key_count >= 2 && key_count.is_multiple_of(2) {
+        let mut types = stage_column_types(
+            key_count / 2,
+            &SpecialStyle {
+                split_stages: false,
+                ..style.clone()
+            },
+        );
+        types.extend_from_within(..);
+        return types;
+    }
+    if key_count % 2 == 1 || style.style_type == 0 {
+        return column_types(key_count);
+    }
+
+    let alternating = (0..key_count - 1).map(|column| {
+        if column.is_multiple_of(2) {
+            ColumnType::Odd
+        } else {
+            ColumnType::Even
+        }
+    });
+    let mut types: Vec<ColumnType> = std::iter::once(ColumnType::Special)
+        .chain(alternating)
+        .collect();
+    if style.style_type == 2 {
+        types.reverse();
+    }
+    types
+}
+
+/// A per-column image of a `[Mania]` block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnImage {
+    /// `KeyImage#`
+    Key,
+    /// `KeyImage#D`
+    KeyPressed,
+    /// `NoteImage#`
+    Note,
+    /// `NoteImage#H`
+    HoldHead,
+    /// `NoteImage#L`
+    HoldBody,
+    /// `NoteImage#T`
+    HoldTail,
+}
+
+impl ColumnImage {
+    pub const ALL: [ColumnImage; 6] = [
+        ColumnImage::Key,
+        ColumnImage::KeyPressed,
+        ColumnImage::Note,
+        ColumnImage::HoldHead,
+        ColumnImage::HoldBody,
+        ColumnImage::HoldTail,
+    ];
+
+    /// The image of the default skin for a column of type `column`, as in `mania-key1D`.
+    pub fn default_name(self, column: ColumnType) -> String {
+        let (prefix, part) = match self {
+            ColumnImage::Key => ("mania-key", ""),
+            ColumnImage::KeyPressed => ("mania-key", "D"),
+            ColumnImage::Note => ("mania-note", ""),
+            ColumnImage::HoldHead => ("mania-note", "H"),
+            ColumnImage::HoldBody => ("mania-note", "L"),
+            ColumnImage::HoldTail => ("mania-note", "T"),
+        };
+        format!("{}{}{}", prefix, column.suffix(), part)
+    }
+
+    /// The names the skin gives this image, one per column.
+    fn configured(self, images: &Images) -> &[String] {
+        match self {
+            ColumnImage::Key => &images.keys.normal,
+            ColumnImage::KeyPressed => &images.keys.pressed,
+            ColumnImage::Note => &images.notes.regular,
+            ColumnImage::HoldHead => &images.notes.hold_head,
+            ColumnImage::HoldBody => &images.notes.hold_body,
+            ColumnImage::HoldTail => &images.notes.hold_tail,
+        }
+    }
+}
+
+impl ManiaConfig {
+    /// Column types of this layout, see `stage_column_types`.
+    pub fn column_types(&self) -> Vec<ColumnType> {
+        stage_column_types(self.keys.count, &self.special_style)
+    }
+
+    /// The image osu! draws for `image` in `column` (0-based): the skin's own name, or
+    /// the default skin's one for the column's type. `None` past the last column.
+    pub fn column_image(&self, column: usize, image: ColumnImage) -> Option<String> {
+        let column_type = *self.column_types().get(column)?;
+        Some(resolve(
+            image,
+            image.configured(&self.images).get(column),
+            column_type,
+        ))
+    }
+
+    /// Every image of the block, with the default skin's names for the ones it leaves
+    /// out.
+    pub fn effective_images(&self) -> Images {
+        let types = self.column_types();
+        let defaults = images_for(&types);
+        let names = |image: ColumnImage| -> Vec<String> {
+            let configured = image.configured(&self.images);
+            types
+                .iter()
+                .enumerate()
+                .map(|(column, &column_type)| resolve(image, configured.get(column), column_type))
+                .collect()
+        };
+        let or = |name: &String, default: &String| -> String {
+            if name.is_empty() { default } else { name }.clone()
+        };
+        let (stage, hits) = (&self.images.stage, &self.images.hits);
+
+        Images {
+            keys: KeyImages {
+                normal: names(ColumnImage::Key),
+                pressed: names(ColumnImage::KeyPressed),
+            },
+            notes: NoteImages {
+                regular: names(ColumnImage::Note),
+                hold_head: names(ColumnImage::HoldHead),
+                hold_body: names(ColumnImage::HoldBody),
+                hold_tail: names(ColumnImage::HoldTail),
+            },
+            stage: StageImages {
+                left: or(&stage.left, &defaults.stage.left),
+                right: or(&stage.right, &defaults.stage.right),
+                bottom: or(&stage.bottom, &defaults.stage.bottom),
+                hint: or(&stage.hint, &defaults.stage.hint),
+                light: or(&stage.light, &defaults.stage.light),
+                lighting_n: or(&stage.lighting_n, &defaults.stage.lighting_n),
+                lighting_l: or(&stage.lighting_l, &defaults.stage.lighting_l),
+                warning_arrow: or(&stage.warning_arrow, &defaults.stage.warning_arrow),
+            },
+            hits: HitImages {
+                hit_0: or(&hits.hit_0, &defaults.hits.hit_0),
+                hit_50: or(&hits.hit_50, &defaults.hits.hit_50),
+                hit_100: or(&hits.hit_100, &defaults.hits.hit_100),
+                hit_200: or(&hits.hit_200, &defaults.hits.hit_200),
+                hit_300: or(&hits.hit_300, &defaults.hits.hit_300),
+                hit_300g: or(&hits.hit_300g, &defaults.hits.hit_300g),
+            },
+        }
+    }
+}
+
+/// An empty or missing name is the default skin's.
+fn resolve(image: ColumnImage, configured: Option<&String>, column: ColumnType) -> String {
+    match configured {
+        Some(name) if !name.is_empty() => name.clone(),
+        _ => image.default_name(column),
+    }
+}
+
+/// The images of the default skin for a `key_count` keys stage.
+pub fn default_images(key_count: u32) -> Images {
+    images_for(&column_types(key_count))
+}
+
+fn images_for(types: &[ColumnType]) -> Images {
+    let names = |image: ColumnImage| -> Vec<String> {
+        types
+            .iter()
+            .map(|&column| image.default_name(column))
+            .collect()
+    };
+
+    Images {
+        keys: KeyImages {
+            normal: names(ColumnImage::Key),
+            pressed: names(ColumnImage::KeyPressed),
+        },
+        notes: NoteImages {
+            regular: names(ColumnImage::Note),
+            hold_head: names(ColumnImage::HoldHead),
+            hold_body: names(ColumnImage::HoldBody),
+            hold_tail: names(ColumnImage::HoldTail),
+        },
+        stage: StageImages {
+            left: String::from("mania-stage-left"),
+            right: String::from("mania-stage-right"),
+            bottom: String::from("mania-stage-bottom"),
+            hint: String::from("mania-stage-hint"),
+            light: String::from("mania-stage-light"),
+            lighting_n: String::from("lightingN"),
+            lighting_l: String::from("lightingL"),
+            warning_arrow: String::from("mania-warningarrow"),
+        },
+        hits: HitImages {
+            hit_0: String::from("mania-hit0"),
+            hit_50: String::from("mania-hit50"),
+            hit_100: String::from("mania-hit100"),
+            hit_200: String::from("mania-hit200"),
+            hit_300: String::from("mania-hit300"),
+            hit_300g: String::from("mania-hit300g"),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::parser::read_mania_config_from_str;
+
+    fn pattern(key_count: u32) -> String {
+        column_types(key_count)
+            .into_iter()
+            .map(ColumnType::suffix)
+            .collect()
+    }
+
+    #[test]
+    fn test_key_count_defaults() {
+        for (key_count, width) in [(1, 30), (4, 30), (7, 30), (10, 30), (17, 28), (18, 26)] {
+            let defaults = key_count_defaults(key_count);
+            let columns = key_count as usize;
+            assert_eq!(
+                defaults.column_width,
+                vec![width; columns],
+                "{}K",
+                key_count
+            );
+            assert_eq!(defaults.column_spacing.len(), columns - 1);
+            assert_eq!(defaults.column_line_width.len(), columns + 1);
+            assert_eq!(defaults.colours, vec![COLUMN_COLOUR; columns]);
+            assert_eq!(defaults.light_colours, vec![LIGHT_COLOUR; columns]);
+        }
+    }
+
+    #[test]
+    fn test_default_stage_fits_the_screen() {
+        for key_count in 1..=MAX_KEY_COUNT {
+            let defaults = key_count_defaults(key_count);
+            let stage: u32 = defaults.column_width.iter().sum::<u32>()
+                + defaults.column_spacing.iter().sum::<u32>();
+            assert!(
+                COLUMN_START + stage + COLUMN_RIGHT <= 640,
+                "{}K is {} wide",
+                key_count,
+                stage
+            );
+        }
+    }
+
+    #[test]
+    fn test_column_types() {
+        assert_eq!(pattern(1), "S");
+        assert_eq!(pattern(4), "1221");
+        assert_eq!(pattern(7), "121S121");
+        assert_eq!(pattern(10), "1212112121");
+        assert_eq!(pattern(18).len(), 18);
+    }
+
+    #[test]
+    fn test_stage_column_types() {
+        let style = |style_type, split_stages| SpecialStyle {
+            style_type,
+            split_stages,
+            stage_separation: 0,
+            separate_score: false,
+        };
+        let pattern = |key_count, style: SpecialStyle| -> String {
+            stage_column_types(key_count, &style)
+                .into_iter()
+                .map(ColumnType::suffix)
+                .collect()
+        };
+
+        assert_eq!(pattern(8, style(0, false)), "12122121");
+        assert_eq!(pattern(8, style(1, false)), "S1212121");
+        assert_eq!(pattern(8, style(2, false)), "1212121S");
+        assert_eq!(pattern(7, style(1, false)), "121S121");
+        assert_eq!(pattern(10, style(0, true)), "12S2112S21");
+        assert_eq!(pattern(16, style(1, true)), "S1212121S1212121");
+    }
+
+    #[test]
+    fn test_column_image() {
+        let configs = read_mania_config_from_str(
+            "[Mania]\nKeys: 4\nKeyImage1: custom/key\nStageHint: custom/hint\n",
+        )
+        .unwrap();
+        let config = &configs[0];
+
+        assert_eq!(
+            config.column_image(0, ColumnImage::Key).unwrap(),
+            "mania-key1"
+        );
+        assert_eq!(
+            config.column_image(1, ColumnImage::Key).unwrap(),
+            "custom/key"
+        );
+        assert_eq!(
+            config.column_image(1, ColumnImage::KeyPressed).unwrap(),
+            "mania-key2D"
+        );
+        assert_eq!(
+            config.column_image(3, ColumnImage::HoldTail).unwrap(),
+            "mania-note1T"
+        );
+        assert!(config.column_image(4, ColumnImage::Note).is_none());
+
+        let images = config.effective_images();
+        assert_eq!(
+            images.keys.normal,
+            ["mania-key1", "custom/key", "mania-key2", "mania-key1"]
+        );
+        assert_eq!(images.stage.hint, "custom/hint");
+        assert_eq!(images.stage.left, "mania-stage-left");
+        assert_eq!(images.hits.hit_300g, "mania-hit300g");
+    }
+
+    #[test]
+    fn test_default_images() {
+        let images = default_images(7);
+        assert_eq!(images.keys.normal[3], "mania-keyS");
+        assert_eq!(images.keys.pressed[1], "mania-key2D");
+        assert_eq!(images.notes.hold_body[0], "mania-note1L");
+        assert_eq!(images.hits.hit_300g, "mania-hit300g");
+    }
+}
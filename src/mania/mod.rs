@@ -1 +1,4 @@
-mod structs;
\ No newline at end of file
+pub mod defaults;
+pub mod parser;
+pub mod structs;
+pub mod writer;
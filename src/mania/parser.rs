@@ -1,25 +1,103 @@
-use ini::{Ini, ParseError, Properties};
-
-use crate::structs::{
+use std::io::Read;
+
+use crate::diagnostic::{Diagnostic, DiagnosticKind, Diagnostics};
+use crate::encoding::decode;
+use crate::error::SkinError;
+use crate::ini::{Ini, Properties};
+use crate::mania::defaults;
+use crate::mania::structs::{
     Behavior, Colours, ColumnLayout, FlipConfig, HitImages, Images, KeyImages, Keys, LightingWidth,
     ManiaConfig, NoteBodyStyle, NoteFlipPerColumn, NoteImages, Positions, SpecialStyle,
     StageImages,
 };
-use std::collections::HashMap;
-use std::error::Error;
-use std::hash::Hash;
-use std::path::PathBuf;
-
-pub fn open_ini(path: &str) -> Result<Ini, Box<dyn Error>> {
-    let content = std::fs::read_to_string(path)?;
-    let escaped_content = content
-        .lines()
-        .filter(|line| !line.trim().starts_with("//")) // Filtrer les lignes commençant par //
-        .collect::<Vec<&str>>()
-        .join("\n")
-        .replace('\\', "\\\\");
+use crate::parser::{check_keys, get_colour, get_flag, get_last, get_value, get_with};
+use crate::source::SourceMap;
+
+const SECTION: &str = "Mania";
+
+/// Highest key count osu! can configure.
+pub const MAX_KEY_COUNT: u32 = 18;
+
+const MANIA_KEYS: &[&str] = &[
+    "Keys",
+    "ColumnStart",
+    "ColumnRight",
+    "ColumnSpacing",
+    "ColumnWidth",
+    "ColumnLineWidth",
+    "BarlineHeight",
+    "LightingNWidth",
+    "LightingLWidth",
+    "WidthForNoteHeightScale",
+    "HitPosition",
+    "LightPosition",
+    "ScorePosition",
+    "ComboPosition",
+    "JudgementLine",
+    "LightFramePerSecond",
+    "SpecialStyle",
+    "ComboBurstStyle",
+    "SplitStages",
+    "StageSeparation",
+    "SeparateScore",
+    "KeysUnderNotes",
+    "UpsideDown",
+    "KeyFlip",
+    "NoteFlip",
+    "KeyFlipWhenUpsideDown",
+    "NoteFlipWhenUpsideDown",
+    "NoteBodyStyle",
+    "ColourColumnLine",
+    "ColourBarline",
+    "ColourJudgementLine",
+    "ColourKeyWarning",
+    "ColourHold",
+    "ColourBreak",
+    "StageLeft",
+    "StageRight",
+    "StageBottom",
+    "StageHint",
+    "StageLight",
+    "LightingN",
+    "LightingL",
+    "StageLightingN",
+    "StageLightingL",
+    "WarningArrow",
+    "Hit0",
+    "Hit50",
+    "Hit100",
+    "Hit200",
+    "Hit300",
+    "Hit300g",
+];
+
+/// Per-column keys: prefix, suffixes allowed after the column number and
+/// number of the first column (`Colour#` counts from 1, the rest from 0).
+const MANIA_COLUMN_KEYS: &[(&str, &[&str], u32)] = &[
+    ("KeyImage", &["", "D"], 0),
+    ("NoteImage", &["", "H", "L", "T"], 0),
+    ("KeyFlipWhenUpsideDown", &["", "D"], 0),
+    ("NoteFlipWhenUpsideDown", &["", "H", "L", "T"], 0),
+    ("KeyFlip", &[""], 0),
+    ("NoteFlip", &["", "H", "L", "T"], 0),
+    ("NoteBodyStyle", &[""], 0),
+    ("ColourLight", &[""], 1),
+    ("Colour", &[""], 1),
+];
+
+pub fn open_ini(path: &str) -> Result<Ini, SkinError> {
+    Ok(open_ini_with_source_map(path)?.0)
+}
 
-    Ok(Ini::load_from_str(&escaped_content)?)
+/// Like `open_ini`, also returning where each section and key was written and the
+/// detected encoding.
+pub fn open_ini_with_source_map(path: &str) -> Result<(Ini, SourceMap), SkinError> {
+    let (content, encoding) = decode(&std::fs::read(path)?);
+    let ini = Ini::load_from_str(&content);
+    let source_map = SourceMap::scan(&content)
+        .with_file(path)
+        .with_encoding(encoding);
+    Ok((ini, source_map))
 }
 
 pub fn parse_color(color_str: &str) -> Option<[u8; 4]> {
@@ -28,10 +106,10 @@ pub fn parse_color(color_str: &str) -> Option<[u8; 4]> {
         let r = parts[0].trim().parse().ok()?;
         let g = parts[1].trim().parse().ok()?;
         let b = parts[2].trim().parse().ok()?;
-        let a = parts
-            .get(3)
-            .and_then(|s| s.trim().parse().ok())
-            .unwrap_or(255);
+        let a = match parts.get(3) {
+            Some(a) => a.trim().parse().ok()?,
+            None => 255,
+        };
         Some([r, g, b, a])
     } else {
         None
@@ -50,121 +128,125 @@ pub fn parse_color_rgb(color_str: &str) -> Option<[u8; 3]> {
     }
 }
 
-pub fn parse_keys(props: &Properties, key_count: u32) -> Keys {
-    Keys {
+pub fn parse_keys(
+    props: &Properties,
+    key_count: u32,
+    diagnostics: &mut Diagnostics,
+) -> Result<Keys, SkinError> {
+    Ok(Keys {
         count: key_count,
-        keys_under_notes: props
-            .get("KeysUnderNotes")
-            .and_then(|v| v.parse::<u32>().ok())
-            .map(|v| v != 0)
-            .unwrap_or(false),
-    }
+        keys_under_notes: get_flag(props, SECTION, "KeysUnderNotes", diagnostics)?.unwrap_or(false),
+    })
 }
 
-pub fn parse_special_style(props: &Properties) -> SpecialStyle {
-    SpecialStyle {
-        style_type: props
-            .get("SpecialStyle")
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(0),
-        split_stages: props
-            .get("SplitStages")
-            .and_then(|v| v.parse::<u32>().ok())
-            .map(|v| v != 0)
-            .unwrap_or(false),
-        stage_separation: props
-            .get("StageSeparation")
-            .and_then(|v| v.parse().ok())
+pub fn parse_special_style(
+    props: &Properties,
+    diagnostics: &mut Diagnostics,
+) -> Result<SpecialStyle, SkinError> {
+    Ok(SpecialStyle {
+        style_type: get_with(props, SECTION, "SpecialStyle", parse_style, diagnostics)?
             .unwrap_or(0),
-        separate_score: props
-            .get("SeparateScore")
-            .and_then(|v| v.parse::<u32>().ok())
-            .map(|v| v != 0)
-            .unwrap_or(false),
-    }
+        split_stages: get_flag(props, SECTION, "SplitStages", diagnostics)?.unwrap_or(false),
+        stage_separation: get_value(props, SECTION, "StageSeparation", diagnostics)?.unwrap_or(0),
+        separate_score: get_flag(props, SECTION, "SeparateScore", diagnostics)?.unwrap_or(false),
+    })
 }
 
-pub fn parse_column_layout(props: &Properties, key_count: u32) -> ColumnLayout {
-    ColumnLayout {
-        column_start: props
-            .get("ColumnStart")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0),
-        column_right: props
-            .get("ColumnRight")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0),
-        column_width: parse_comma_separated_numbers(props.get("ColumnWidth")),
-        column_spacing: parse_comma_separated_numbers(props.get("ColumnSpacing")),
-        column_line_width: parse_comma_separated_numbers(props.get("ColumnLineWidth")),
-        barline_height: props
-            .get("BarlineHeight")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(1.0),
-        lighting_width: parse_lighting_width(props),
-        width_for_note_height_scale: props
-            .get("WidthForNoteHeightScale")
-            .and_then(|s| s.parse().ok()),
-    }
+pub fn parse_column_layout(
+    props: &Properties,
+    key_count: u32,
+    diagnostics: &mut Diagnostics,
+) -> Result<ColumnLayout, SkinError> {
+    let columns = defaults::key_count_defaults(key_count);
+    Ok(ColumnLayout {
+        column_start: get_value(props, SECTION, "ColumnStart", diagnostics)?
+            .unwrap_or(defaults::COLUMN_START),
+        column_right: get_value(props, SECTION, "ColumnRight", diagnostics)?
+            .unwrap_or(defaults::COLUMN_RIGHT),
+        column_width: get_column_list(props, "ColumnWidth", &columns.column_width, diagnostics)?,
+        column_spacing: get_column_list(
+            props,
+            "ColumnSpacing",
+            &columns.column_spacing,
+            diagnostics,
+        )?,
+        column_line_width: get_column_list(
+            props,
+            "ColumnLineWidth",
+            &columns.column_line_width,
+            diagnostics,
+        )?,
+        barline_height: get_value(props, SECTION, "BarlineHeight", diagnostics)?.unwrap_or(1.0),
+        lighting_width: parse_lighting_width(props, diagnostics)?,
+        width_for_note_height_scale: get_value(
+            props,
+            SECTION,
+            "WidthForNoteHeightScale",
+            diagnostics,
+        )?,
+    })
 }
 
-pub fn parse_lighting_width(props: &Properties) -> LightingWidth {
-    LightingWidth {
-        n_width: parse_comma_separated_numbers(props.get("LightingNWidth")),
-        l_width: parse_comma_separated_numbers(props.get("LightingLWidth")),
-    }
+pub fn parse_lighting_width(
+    props: &Properties,
+    diagnostics: &mut Diagnostics,
+) -> Result<LightingWidth, SkinError> {
+    Ok(LightingWidth {
+        n_width: get_number_list(props, "LightingNWidth", diagnostics)?,
+        l_width: get_number_list(props, "LightingLWidth", diagnostics)?,
+    })
 }
 
-pub fn parse_positions(props: &Properties) -> Positions {
-    Positions {
-        hit_position: props
-            .get("HitPosition")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0),
-        light_position: props
-            .get("LightPosition")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0),
-        score_position: props
-            .get("ScorePosition")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0),
-        combo_position: props
-            .get("ComboPosition")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0),
-    }
+pub fn parse_positions(
+    props: &Properties,
+    diagnostics: &mut Diagnostics,
+) -> Result<Positions, SkinError> {
+    Ok(Positions {
+        hit_position: get_value(props, SECTION, "HitPosition", diagnostics)?
+            .unwrap_or(defaults::HIT_POSITION),
+        light_position: get_value(props, SECTION, "LightPosition", diagnostics)?
+            .unwrap_or(defaults::LIGHT_POSITION),
+        score_position: get_value(props, SECTION, "ScorePosition", diagnostics)?
+            .unwrap_or(defaults::SCORE_POSITION),
+        combo_position: get_value(props, SECTION, "ComboPosition", diagnostics)?
+            .unwrap_or(defaults::COMBO_POSITION),
+    })
 }
 
-pub fn parse_colours(props: &Properties, key_count: u32) -> Colours {
-    Colours {
-        columns: parse_numbered_colors(props, "Colour", key_count),
-        lights: parse_numbered_colors(props, "ColourLight", key_count),
-        column_line: props
-            .get("ColourColumnLine")
-            .and_then(parse_color)
+pub fn parse_colours(
+    props: &Properties,
+    key_count: u32,
+    diagnostics: &mut Diagnostics,
+) -> Result<Colours, SkinError> {
+    let columns = defaults::key_count_defaults(key_count);
+    Ok(Colours {
+        columns: parse_numbered_colors(props, "Colour", &columns.colours, diagnostics)?,
+        lights: parse_numbered_colors(props, "ColourLight", &columns.light_colours, diagnostics)?,
+        column_line: get_colour(props, SECTION, "ColourColumnLine", parse_color, diagnostics)?
             .unwrap_or([255, 255, 255, 255]),
-        barline: props
-            .get("ColourBarline")
-            .and_then(parse_color)
+        barline: get_colour(props, SECTION, "ColourBarline", parse_color, diagnostics)?
             .unwrap_or([255, 255, 255, 255]),
-        judgement_line: props
-            .get("ColourJudgementLine")
-            .and_then(parse_color_rgb)
-            .unwrap_or([255, 255, 255]),
-        key_warning: props
-            .get("ColourKeyWarning")
-            .and_then(parse_color_rgb)
-            .unwrap_or([255, 0, 0]),
-        hold: props
-            .get("ColourHold")
-            .and_then(parse_color)
+        judgement_line: get_colour(
+            props,
+            SECTION,
+            "ColourJudgementLine",
+            parse_color_rgb,
+            diagnostics,
+        )?
+        .unwrap_or([255, 255, 255]),
+        key_warning: get_colour(
+            props,
+            SECTION,
+            "ColourKeyWarning",
+            parse_color_rgb,
+            diagnostics,
+        )?
+        .unwrap_or([255, 0, 0]),
+        hold: get_colour(props, SECTION, "ColourHold", parse_color, diagnostics)?
             .unwrap_or([255, 230, 0, 255]),
-        break_colour: props
-            .get("ColourBreak")
-            .and_then(parse_color_rgb)
+        break_colour: get_colour(props, SECTION, "ColourBreak", parse_color_rgb, diagnostics)?
             .unwrap_or([255, 0, 0]),
-    }
+    })
 }
 
 pub fn parse_images(props: &Properties, key_count: u32) -> Images {
@@ -175,17 +257,16 @@ pub fn parse_images(props: &Properties, key_count: u32) -> Images {
         hits: parse_hit_images(props),
     }
 }
-
 pub fn parse_key_images(props: &Properties, key_count: u32) -> KeyImages {
     KeyImages {
-        normal: parse_numbered_strings(props, "KeyImage", key_count,Some("")),
+        normal: parse_numbered_strings(props, "KeyImage", key_count, Some("")),
         pressed: parse_numbered_strings(props, "KeyImage", key_count, Some("D")),
     }
 }
 
 pub fn parse_note_images(props: &Properties, key_count: u32) -> NoteImages {
     NoteImages {
-        regular: parse_numbered_strings(props, "NoteImage", key_count,Some("")),
+        regular: parse_numbered_strings(props, "NoteImage", key_count, Some("")),
         hold_head: parse_numbered_strings(props, "NoteImage", key_count, Some("H")),
         hold_body: parse_numbered_strings(props, "NoteImage", key_count, Some("L")),
         hold_tail: parse_numbered_strings(props, "NoteImage", key_count, Some("T")),
@@ -194,30 +275,30 @@ pub fn parse_note_images(props: &Properties, key_count: u32) -> NoteImages {
 
 pub fn parse_stage_images(props: &Properties) -> StageImages {
     StageImages {
-        left: props.get("StageLeft").map(String::from).unwrap_or_default(),
-        right: props
-            .get("StageRight")
+        left: get_last(props, "StageLeft")
+            .map(String::from)
+            .unwrap_or_default(),
+        right: get_last(props, "StageRight")
+            .map(String::from)
+            .unwrap_or_default(),
+        bottom: get_last(props, "StageBottom")
             .map(String::from)
             .unwrap_or_default(),
-        bottom: props
-            .get("StageBottom")
+        hint: get_last(props, "StageHint")
             .map(String::from)
             .unwrap_or_default(),
-        hint: props.get("StageHint").map(String::from).unwrap_or_default(),
-        light: props
-            .get("StageLight")
+        light: get_last(props, "StageLight")
             .map(String::from)
             .unwrap_or_default(),
-        lighting_n: props
-            .get("StageLightingN")
+        lighting_n: get_last(props, "LightingN")
+            .or_else(|| get_last(props, "StageLightingN"))
             .map(String::from)
             .unwrap_or_default(),
-        lighting_l: props
-            .get("StageLightingL")
+        lighting_l: get_last(props, "LightingL")
+            .or_else(|| get_last(props, "StageLightingL"))
             .map(String::from)
             .unwrap_or_default(),
-        warning_arrow: props
-            .get("WarningArrow")
+        warning_arrow: get_last(props, "WarningArrow")
             .map(String::from)
             .unwrap_or_default(),
     }
@@ -225,74 +306,102 @@ pub fn parse_stage_images(props: &Properties) -> StageImages {
 
 pub fn parse_hit_images(props: &Properties) -> HitImages {
     HitImages {
-        hit_0: props.get("Hit0").map(String::from).unwrap_or_default(),
-        hit_50: props.get("Hit50").map(String::from).unwrap_or_default(),
-        hit_100: props.get("Hit100").map(String::from).unwrap_or_default(),
-        hit_200: props.get("Hit200").map(String::from).unwrap_or_default(),
-        hit_300: props.get("Hit300").map(String::from).unwrap_or_default(),
-        hit_300g: props.get("Hit300g").map(String::from).unwrap_or_default(),
-    }
-}
-
-pub fn parse_behavior(props: &Properties, key_count: u32) -> Behavior {
-    Behavior {
-        judgement_line: props
-            .get("JudgementLine")
-            .and_then(|v| v.parse::<u32>().ok())
-            .map(|v| v != 0)
-            .unwrap_or(false),
-        light_frame_per_second: props
-            .get("LightFramePerSecond")
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(24),
-        upside_down: props
-            .get("UpsideDown")
-            .and_then(|v| v.parse::<u32>().ok())
-            .map(|v| v != 0)
-            .unwrap_or(false),
-        note_body_style: parse_note_body_style(props, key_count),
-        flip_config: parse_flip_config(props, key_count),
-        combo_burst_style: props
-            .get("ComboBurstStyle")
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(0),
+        hit_0: get_last(props, "Hit0")
+            .map(String::from)
+            .unwrap_or_default(),
+        hit_50: get_last(props, "Hit50")
+            .map(String::from)
+            .unwrap_or_default(),
+        hit_100: get_last(props, "Hit100")
+            .map(String::from)
+            .unwrap_or_default(),
+        hit_200: get_last(props, "Hit200")
+            .map(String::from)
+            .unwrap_or_default(),
+        hit_300: get_last(props, "Hit300")
+            .map(String::from)
+            .unwrap_or_default(),
+        hit_300g: get_last(props, "Hit300g")
+            .map(String::from)
+            .unwrap_or_default(),
     }
 }
 
-pub fn parse_note_body_style(props: &Properties, key_count: u32) -> NoteBodyStyle {
-    NoteBodyStyle {
-        global: props
-            .get("NoteBodyStyle")
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(0),
-        per_column: parse_numbered_values(props, "NoteBodyStyle", key_count, 0),
-    }
+pub fn parse_behavior(
+    props: &Properties,
+    key_count: u32,
+    diagnostics: &mut Diagnostics,
+) -> Result<Behavior, SkinError> {
+    Ok(Behavior {
+        judgement_line: get_flag(props, SECTION, "JudgementLine", diagnostics)?.unwrap_or(false),
+        light_frame_per_second: get_value(props, SECTION, "LightFramePerSecond", diagnostics)?
+            .unwrap_or(24),
+        upside_down: get_flag(props, SECTION, "UpsideDown", diagnostics)?.unwrap_or(false),
+        note_body_style: parse_note_body_style(props, key_count, diagnostics)?,
+        flip_config: parse_flip_config(props, key_count, diagnostics)?,
+        combo_burst_style: get_value(props, SECTION, "ComboBurstStyle", diagnostics)?.unwrap_or(0),
+    })
 }
 
-pub fn parse_flip_config(props: &Properties, key_count: u32) -> FlipConfig {
-    FlipConfig {
-        key_flip: props
-            .get("KeyFlip")
-            .and_then(|v| v.parse::<u32>().ok())
-            .map(|v| v != 0)
-            .unwrap_or(false),
-        note_flip: props
-            .get("NoteFlip")
-            .and_then(|v| v.parse::<u32>().ok())
-            .map(|v| v != 0)
-            .unwrap_or(false),
-        per_column_key_flip: parse_numbered_bools(props, "KeyFlip", key_count),
-        per_column_note_flip: parse_note_flip_per_column(props, key_count),
-    }
+pub fn parse_note_body_style(
+    props: &Properties,
+    key_count: u32,
+    diagnostics: &mut Diagnostics,
+) -> Result<NoteBodyStyle, SkinError> {
+    let global = get_with(props, SECTION, "NoteBodyStyle", parse_style, diagnostics)?.unwrap_or(0);
+    // Columns without a value follow the global style
+    let per_column = (0..key_count)
+        .map(|i| {
+            let key = format!("NoteBodyStyle{}", i);
+            get_with(props, SECTION, &key, parse_style, diagnostics)
+                .map(|style| style.unwrap_or(global))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(NoteBodyStyle { global, per_column })
 }
 
-pub fn parse_note_flip_per_column(props: &Properties, key_count: u32) -> NoteFlipPerColumn {
-    NoteFlipPerColumn {
-        note: parse_numbered_bools(props, "NoteFlip", key_count),
-        hold_head: parse_numbered_bools(props, "NoteFlipH", key_count),
-        hold_body: parse_numbered_bools(props, "NoteFlipL", key_count),
-        hold_tail: parse_numbered_bools(props, "NoteFlipT", key_count),
-    }
+pub fn parse_flip_config(
+    props: &Properties,
+    key_count: u32,
+    diagnostics: &mut Diagnostics,
+) -> Result<FlipConfig, SkinError> {
+    Ok(FlipConfig {
+        key_flip: get_flag(props, SECTION, "KeyFlip", diagnostics)?.unwrap_or(false),
+        note_flip: get_flag(props, SECTION, "NoteFlip", diagnostics)?.unwrap_or(false),
+        per_column_key_flip: parse_numbered_bools(props, "KeyFlip", key_count, diagnostics)?,
+        per_column_note_flip: parse_note_flip_per_column(props, key_count, diagnostics)?,
+    })
+}
+
+pub fn parse_note_flip_per_column(
+    props: &Properties,
+    key_count: u32,
+    diagnostics: &mut Diagnostics,
+) -> Result<NoteFlipPerColumn, SkinError> {
+    Ok(NoteFlipPerColumn {
+        note: parse_numbered_bools(props, "NoteFlip", key_count, diagnostics)?,
+        hold_head: parse_numbered_bools_with_suffix(
+            props,
+            "NoteFlip",
+            key_count,
+            "H",
+            diagnostics,
+        )?,
+        hold_body: parse_numbered_bools_with_suffix(
+            props,
+            "NoteFlip",
+            key_count,
+            "L",
+            diagnostics,
+        )?,
+        hold_tail: parse_numbered_bools_with_suffix(
+            props,
+            "NoteFlip",
+            key_count,
+            "T",
+            diagnostics,
+        )?,
+    })
 }
 
 pub fn parse_comma_separated_numbers(value: Option<&str>) -> Vec<u32> {
@@ -301,6 +410,14 @@ pub fn parse_comma_separated_numbers(value: Option<&str>) -> Vec<u32> {
         .unwrap_or_default()
 }
 
+/// Strict counterpart of `parse_comma_separated_numbers`, `None` if any item is not a number.
+pub fn parse_number_list(value: &str) -> Option<Vec<u32>> {
+    if value.trim().is_empty() {
+        return Some(Vec::new());
+    }
+    value.split(',').map(|n| n.trim().parse().ok()).collect()
+}
+
 pub fn parse_numbered_strings(
     props: &Properties,
     prefix: &str,
@@ -314,14 +431,26 @@ pub fn parse_numbered_strings(
             } else {
                 format!("{}{}", prefix, i)
             };
-            props.get(&key).map(String::from).unwrap_or_default()
+            get_last(props, &key).map(String::from).unwrap_or_default()
         })
         .collect()
 }
 
-pub fn parse_numbered_colors(props: &Properties, prefix: &str, count: u32) -> Vec<[u8; 4]> {
-    (1..=count)
-        .filter_map(|i| props.get(&format!("{}{}", prefix, i)).and_then(parse_color))
+/// One colour per column of `defaults`, with its default for the columns the skin
+/// leaves out.
+pub fn parse_numbered_colors(
+    props: &Properties,
+    prefix: &str,
+    defaults: &[[u8; 4]],
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<[u8; 4]>, SkinError> {
+    (1..)
+        .zip(defaults)
+        .map(|(i, default)| {
+            let key = format!("{}{}", prefix, i);
+            get_colour(props, SECTION, &key, parse_color, diagnostics)
+                .map(|color| color.unwrap_or(*default))
+        })
         .collect()
 }
 
@@ -330,67 +459,260 @@ pub fn parse_numbered_values<T: std::str::FromStr + Clone>(
     prefix: &str,
     count: u32,
     default: T,
-) -> Vec<T> {
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<T>, SkinError> {
     (0..count)
         .map(|i| {
-            props
-                .get(&format!("{}{}", prefix, i))
-                .and_then(|v| v.parse().ok())
-                .unwrap_or_else(|| default.clone())
+            get_value(props, SECTION, &format!("{}{}", prefix, i), diagnostics)
+                .map(|v| v.unwrap_or_else(|| default.clone()))
         })
         .collect()
 }
 
+pub fn parse_numbered_bools(
+    props: &Properties,
+    prefix: &str,
+    count: u32,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<bool>, SkinError> {
+    parse_numbered_bools_with_suffix(props, prefix, count, "", diagnostics)
+}
 
-pub fn parse_numbered_bools(props: &Properties, prefix: &str, count: u32) -> Vec<bool> {
-    parse_numbered_values(props, prefix, count, false)
+fn parse_numbered_bools_with_suffix(
+    props: &Properties,
+    prefix: &str,
+    count: u32,
+    suffix: &str,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<bool>, SkinError> {
+    (0..count)
+        .map(|i| {
+            get_flag(
+                props,
+                SECTION,
+                &format!("{}{}{}", prefix, i, suffix),
+                diagnostics,
+            )
+            .map(|v| v.unwrap_or(false))
+        })
+        .collect()
 }
 
-pub fn read_mania_config(ini: &Ini) -> Vec<ManiaConfig> {
-    let mut configs = Vec::new();
+fn get_number_list(
+    props: &Properties,
+    key: &str,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<u32>, SkinError> {
+    Ok(get_with(props, SECTION, key, parse_number_list, diagnostics)?.unwrap_or_default())
+}
 
-    for section in ini.sections() {
-        if section.as_deref() == Some("Mania") {
-            if let Some(props) = ini.section(section) {
-                if let Some(keys_str) = props.get("Keys") {
-                    if let Ok(key_count) = keys_str.parse::<u32>() {
-                        configs.push(ManiaConfig {
-                            keys: parse_keys(props, key_count),
-                            special_style: parse_special_style(props),
-                            column_layout: parse_column_layout(props, key_count),
-                            positions: parse_positions(props),
-                            colours: parse_colours(props, key_count),
-                            images: parse_images(props, key_count),
-                            behavior: parse_behavior(props, key_count),
-                        });
-                    }
-                }
+/// Reads a per-column list, padding it with the rest of `defaults` like osu! does.
+/// A missing list is `defaults`.
+fn get_column_list(
+    props: &Properties,
+    key: &str,
+    defaults: &[u32],
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<u32>, SkinError> {
+    let mut list = get_number_list(props, key, diagnostics)?;
+    let expected = defaults.len();
+    if list.is_empty() {
+        return Ok(defaults.to_vec());
+    }
+    if list.len() < expected {
+        let value = get_last(props, key).unwrap_or_default();
+        let kind = DiagnosticKind::ShortList {
+            expected,
+            found: list.len(),
+        };
+        diagnostics.report(Diagnostic::new(SECTION, key, value, kind))?;
+        list.extend_from_slice(&defaults[list.len()..]);
+    }
+    Ok(list)
+}
+
+/// `SpecialStyle` and `NoteBodyStyle` only have the values 0, 1 and 2.
+fn parse_style(value: &str) -> Option<u8> {
+    value.parse().ok().filter(|style| *style <= 2)
+}
+
+fn parse_key_count(value: &str) -> Option<u32> {
+    value
+        .parse()
+        .ok()
+        .filter(|count| (1..=MAX_KEY_COUNT).contains(count))
+}
+
+/// Column number of a per-column key such as `NoteImage2H` or `Colour3`,
+/// along with the number of the first column of its family.
+fn column_of(key: &str) -> Option<(u32, u32)> {
+    MANIA_COLUMN_KEYS
+        .iter()
+        .find_map(|(prefix, suffixes, first)| {
+            let rest = key.strip_prefix(prefix)?;
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let (number, suffix) = rest.split_at(digits);
+            if number.is_empty() || !suffixes.contains(&suffix) {
+                return None;
+            }
+            Some((number.parse().ok()?, *first))
+        })
+}
+
+/// Reports unknown and duplicate keys, and per-column keys past the last column.
+fn check_mania_keys(
+    props: &Properties,
+    key_count: u32,
+    diagnostics: &mut Diagnostics,
+) -> Result<(), SkinError> {
+    check_keys(
+        props,
+        SECTION,
+        |key| MANIA_KEYS.contains(&key) || column_of(key).is_some(),
+        diagnostics,
+    )?;
+    for (key, value) in props.iter() {
+        match column_of(key) {
+            Some((column, first)) if column < first || column >= first + key_count => {
+                let kind = DiagnosticKind::ColumnOutOfRange { column, key_count };
+                diagnostics.report(Diagnostic::new(SECTION, key, value, kind))?;
             }
+            _ => {}
         }
     }
+    Ok(())
+}
+
+pub fn read_mania_config(
+    ini: &Ini,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<ManiaConfig>, SkinError> {
+    let mut configs = Vec::new();
+
+    // `section()` only returns the first [Mania] block, each key count has its own
+    for (occurrence, props) in ini.section_all(SECTION).enumerate() {
+        diagnostics.enter_section(occurrence);
+        configs.extend(parse_mania_section(props, diagnostics)?);
+    }
+
+    Ok(configs)
+}
 
-    configs
+/// `read_mania_config` for skin.ini text already in memory, failing on the first invalid entry.
+pub fn read_mania_config_from_str(content: &str) -> Result<Vec<ManiaConfig>, SkinError> {
+    let source_map = SourceMap::scan(content);
+    let ini = Ini::load_from_str(content);
+    read_mania_config(
+        &ini,
+        &mut Diagnostics::strict().with_source_map(&source_map),
+    )
+}
+
+/// Like `read_mania_config_from_str`, detecting the encoding of `bytes` first.
+pub fn read_mania_config_from_bytes(bytes: &[u8]) -> Result<Vec<ManiaConfig>, SkinError> {
+    read_mania_config_from_str(&decode(bytes).0)
+}
+
+pub fn read_mania_config_from_reader(mut reader: impl Read) -> Result<Vec<ManiaConfig>, SkinError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    read_mania_config_from_bytes(&bytes)
+}
+
+/// The layout osu! uses for `key_count` keys when the skin has no `[Mania]` block for it,
+/// with the images of the default skin. `None` for 0 or more than `MAX_KEY_COUNT` keys.
+pub fn default_config(key_count: u32) -> Option<ManiaConfig> {
+    let mut props = Properties::new();
+    props.insert("Keys", key_count.to_string());
+    // A block with nothing but `Keys` cannot be invalid
+    let config = parse_mania_section(&props, &mut Diagnostics::strict()).ok()??;
+    Some(ManiaConfig {
+        images: defaults::default_images(key_count),
+        ..config
+    })
+}
+
+/// Parses one `[Mania]` block, `None` when it has no usable key count.
+pub fn parse_mania_section(
+    props: &Properties,
+    diagnostics: &mut Diagnostics,
+) -> Result<Option<ManiaConfig>, SkinError> {
+    let Some(key_count) = get_with(props, SECTION, "Keys", parse_key_count, diagnostics)? else {
+        // osu! skips blocks without a usable key count
+        return Ok(None);
+    };
+    check_mania_keys(props, key_count, diagnostics)?;
+
+    Ok(Some(ManiaConfig {
+        keys: parse_keys(props, key_count, diagnostics)?,
+        special_style: parse_special_style(props, diagnostics)?,
+        column_layout: parse_column_layout(props, key_count, diagnostics)?,
+        positions: parse_positions(props, diagnostics)?,
+        colours: parse_colours(props, key_count, diagnostics)?,
+        images: parse_images(props, key_count),
+        behavior: parse_behavior(props, key_count, diagnostics)?,
+    }))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::error::Error;
 
     #[test]
     fn open_ini_test() -> Result<(), Box<dyn Error>> {
-        let ini = open_ini("./ressources/test.ini")?;
-        let section = ini.section(Some("General")).unwrap();
+        let ini = open_ini("./resources/test.ini")?;
+        let section = ini.section("General").unwrap();
         assert_eq!(section.get("Name"), Some("Aggro"));
         assert_eq!(section.get("Author"), Some("virtual"));
         assert_eq!(section.get("Version"), Some("2.7"));
 
-        let ini = open_ini("./ressources/test2.ini")?;
-        let section = ini.section(Some("General")).unwrap();
-        assert_eq!(section.get("Name"), Some("YUGEN ❯REMASTERED v1.2"));
+        let ini = open_ini("./resources/test2.ini")?;
+        let section = ini.section("General").unwrap();
+        assert_eq!(section.get("Name"), Some("YUGEN REMASTERED v1.2"));
         assert_eq!(section.get("Author"), Some("Garin"));
         assert_eq!(section.get("Version"), Some("2.5"));
         Ok(())
     }
+    #[test]
+    fn test_default_config() {
+        let config = default_config(9).unwrap();
+        assert_eq!(config.keys.count, 9);
+        let layout = &config.column_layout;
+        assert_eq!(layout.column_start, 136);
+        assert_eq!(layout.column_width, vec![30; 9]);
+        assert_eq!(layout.column_spacing, vec![0; 8]);
+        assert_eq!(layout.column_line_width, vec![2; 10]);
+        assert_eq!(config.positions.hit_position, 402);
+        assert_eq!(config.positions.light_position, 413);
+        assert_eq!(config.colours.columns, vec![[0, 0, 0, 255]; 9]);
+        assert_eq!(config.images.keys.normal[4], "mania-keyS");
+        assert_eq!(config.images.notes.regular[5], "mania-note2");
+
+        // Columns the skin leaves out keep their default, the others their own number
+        let configs = read_mania_config_from_str("[Mania]\nKeys: 4\nColour2: 1,2,3\n").unwrap();
+        let columns = &configs[0].colours.columns;
+        assert_eq!(columns[1], [1, 2, 3, 255]);
+        assert_eq!(columns[0], [0, 0, 0, 255]);
+        assert!(configs[0].images.keys.normal[0].is_empty());
+        assert!(default_config(0).is_none());
+        assert!(default_config(MAX_KEY_COUNT + 1).is_none());
+
+        for key_count in [1, 4, 7, 10, 18] {
+            let config = default_config(key_count).unwrap();
+            let defaults = defaults::key_count_defaults(key_count);
+            assert_eq!(config.column_layout.column_width, defaults.column_width);
+            assert_eq!(config.colours.lights, defaults.light_colours);
+        }
+        // A short list is padded with the key count's own defaults
+        let mut props = Properties::new();
+        props.insert("ColumnWidth".to_string(), "20,20".to_string());
+        let layout = parse_column_layout(&props, 18, &mut Diagnostics::lenient()).unwrap();
+        assert_eq!(layout.column_width[..3], [20, 20, 26]);
+    }
+
     #[test]
     fn test_parse_color() {
         assert_eq!(parse_color("255,128,64"), Some([255, 128, 64, 255]));
@@ -422,12 +744,12 @@ mod tests {
         let mut props = Properties::new();
         props.insert("KeysUnderNotes".to_string(), "1".to_string());
 
-        let keys = parse_keys(&props, 4);
+        let keys = parse_keys(&props, 4, &mut Diagnostics::strict()).unwrap();
         assert_eq!(keys.count, 4);
         assert!(keys.keys_under_notes);
 
         let empty_props = Properties::new();
-        let default_keys = parse_keys(&empty_props, 4);
+        let default_keys = parse_keys(&empty_props, 4, &mut Diagnostics::strict()).unwrap();
         assert_eq!(default_keys.count, 4);
         assert!(!default_keys.keys_under_notes);
     }
@@ -440,7 +762,7 @@ mod tests {
         props.insert("StageSeparation".to_string(), "10".to_string());
         props.insert("SeparateScore".to_string(), "1".to_string());
 
-        let style = parse_special_style(&props);
+        let style = parse_special_style(&props, &mut Diagnostics::strict()).unwrap();
         assert_eq!(style.style_type, 2);
         assert!(style.split_stages);
         assert_eq!(style.stage_separation, 10);
@@ -449,8 +771,8 @@ mod tests {
 
     #[test]
     fn test_integration() -> Result<(), Box<dyn Error>> {
-        let ini = open_ini("./ressources/test.ini")?;
-        let configs = read_mania_config(&ini);
+        let ini = open_ini("./resources/test.ini")?;
+        let configs = read_mania_config(&ini, &mut Diagnostics::strict())?;
 
         assert!(
             !configs.is_empty(),
@@ -466,13 +788,143 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_read_mania_config_errors() -> Result<(), Box<dyn Error>> {
+        let ini = Ini::load_from_str("[Mania]\nKeys: 4\nKeyImage4D: key\n");
+        match read_mania_config(&ini, &mut Diagnostics::strict()) {
+            Err(SkinError::ColumnOutOfRange {
+                key,
+                column,
+                key_count,
+                ..
+            }) => {
+                assert_eq!(key, "KeyImage4D");
+                assert_eq!(column, 4);
+                assert_eq!(key_count, 4);
+            }
+            other => panic!("expected an out-of-range column, got {:?}", other.err()),
+        }
+
+        // Colour# is 1-based
+        let ini = Ini::load_from_str("[Mania]\nKeys: 4\nColour4: 0,0,0\nColour0: 0,0,0\n");
+        assert!(matches!(
+            read_mania_config(&ini, &mut Diagnostics::strict()),
+            Err(SkinError::ColumnOutOfRange { column: 0, .. })
+        ));
+
+        let ini = Ini::load_from_str("[Mania]\nKeys: 4\nHitPos: 400\n");
+        assert!(matches!(
+            read_mania_config(&ini, &mut Diagnostics::strict()),
+            Err(SkinError::UnknownKey { key, .. }) if key == "HitPos"
+        ));
+
+        let ini = Ini::load_from_str("[Mania]\nKeys: 19\n");
+        assert!(matches!(
+            read_mania_config(&ini, &mut Diagnostics::strict()),
+            Err(SkinError::InvalidValue { key, value, .. }) if key == "Keys" && value == "19"
+        ));
+
+        let ini = Ini::load_from_str("[Mania]\nKeys: 4\nColourHold: 255,230\n");
+        let err = read_mania_config(&ini, &mut Diagnostics::strict()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "[Mania] ColourHold: invalid value \"255,230\""
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_mania_config_lenient() -> Result<(), Box<dyn Error>> {
+        let ini = Ini::load_from_str(
+            "[Mania]\nKeys: 4\nHitPosition: 400\nHitPosition: 420\nColumnWidth: 40,40\n\
+             ColourHold: 255,230\nNoteBodyStyle: stretch\nKeyImage4: key\nHitPos: 1\n",
+        );
+        let mut diagnostics = Diagnostics::lenient();
+        let configs = read_mania_config(&ini, &mut diagnostics)?;
+        let config = &configs[0];
+        assert_eq!(config.positions.hit_position, 420);
+        assert_eq!(config.column_layout.column_width, vec![40, 40, 30, 30]);
+        assert_eq!(config.colours.hold, [255, 230, 0, 255]);
+        assert_eq!(config.behavior.note_body_style.global, 0);
+
+        let kinds: Vec<(String, DiagnosticKind)> = diagnostics
+            .into_vec()
+            .into_iter()
+            .map(|d| (d.key, d.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("HitPosition".to_string(), DiagnosticKind::DuplicateKey),
+                ("HitPos".to_string(), DiagnosticKind::UnknownKey),
+                (
+                    "KeyImage4".to_string(),
+                    DiagnosticKind::ColumnOutOfRange {
+                        column: 4,
+                        key_count: 4
+                    }
+                ),
+                (
+                    "ColumnWidth".to_string(),
+                    DiagnosticKind::ShortList {
+                        expected: 4,
+                        found: 2
+                    }
+                ),
+                ("ColourHold".to_string(), DiagnosticKind::IncompleteColour),
+                ("NoteBodyStyle".to_string(), DiagnosticKind::InvalidValue),
+            ]
+        );
+
+        // The same file is rejected at the first problem in strict mode
+        assert!(matches!(
+            read_mania_config(&ini, &mut Diagnostics::strict()),
+            Err(SkinError::DuplicateKey { key, .. }) if key == "HitPosition"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_mania_config_from_memory() -> Result<(), Box<dyn Error>> {
+        let bytes = std::fs::read("./resources/test.ini")?;
+        let configs = read_mania_config_from_reader(bytes.as_slice())?;
+        assert_eq!(configs.len(), 1);
+        assert_eq!(configs[0].keys.count, 4);
+
+        let bytes = std::fs::read("./resources/encoding/shift_jis.ini")?;
+        assert_eq!(read_mania_config_from_bytes(&bytes)?[0].keys.count, 4);
+
+        match read_mania_config_from_str("[Mania]\nKeys: 4\n\nHitPosition: abc\n") {
+            Err(SkinError::InvalidValue { key, line, .. }) => {
+                assert_eq!(key, "HitPosition");
+                assert_eq!(line, Some(4));
+            }
+            other => panic!("expected an invalid HitPosition, got {:?}", other.err()),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_ini_unescaped_paths() -> Result<(), Box<dyn Error>> {
+        // Backslashes and malformed headers no longer need special handling
+        let path = std::env::temp_dir().join("rosu-skin-unescaped.ini");
+        std::fs::write(&path, "[Mania\n[Mania]\nKeys: 4\nKeyImage0: keys\\left\n")?;
+        let ini = open_ini(path.to_str().unwrap());
+        std::fs::remove_file(&path)?;
+        let ini = ini?;
+        assert_eq!(ini.section("General").unwrap().get("[Mania"), Some(""));
+        let mania = ini.section("Mania").unwrap();
+        assert_eq!(mania.get("KeyImage0"), Some("keys\\left"));
+        Ok(())
+    }
+
     #[test]
     fn test_parse_lighting_width() {
         let mut props = Properties::new();
         props.insert("LightingNWidth".to_string(), "2,3,4".to_string());
         props.insert("LightingLWidth".to_string(), "5,6,7".to_string());
 
-        let lighting = parse_lighting_width(&props);
+        let lighting = parse_lighting_width(&props, &mut Diagnostics::strict()).unwrap();
         assert_eq!(lighting.n_width, vec![2, 3, 4]);
         assert_eq!(lighting.l_width, vec![5, 6, 7]);
     }
@@ -484,18 +936,58 @@ mod tests {
         props.insert("ColumnRight".to_string(), "20".to_string());
         props.insert("ColumnWidth".to_string(), "32,32,32".to_string());
         props.insert("ColumnSpacing".to_string(), "2,2".to_string());
-        props.insert("ColumnLineWidth".to_string(), "1,1,1".to_string());
+        props.insert("ColumnLineWidth".to_string(), "1,1,1,1".to_string());
         props.insert("BarlineHeight".to_string(), "1.5".to_string());
         props.insert("WidthForNoteHeightScale".to_string(), "100".to_string());
 
-        let layout = parse_column_layout(&props, 3);
+        let layout = parse_column_layout(&props, 3, &mut Diagnostics::strict()).unwrap();
         assert_eq!(layout.column_start, 10);
         assert_eq!(layout.column_right, 20);
         assert_eq!(layout.column_width, vec![32, 32, 32]);
         assert_eq!(layout.column_spacing, vec![2, 2]);
-        assert_eq!(layout.column_line_width, vec![1, 1, 1]);
+        assert_eq!(layout.column_line_width, vec![1, 1, 1, 1]);
         assert_eq!(layout.barline_height, 1.5);
         assert_eq!(layout.width_for_note_height_scale, Some(100));
+
+        // Short lists are padded with the defaults
+        props.insert("ColumnSpacing".to_string(), "2".to_string());
+        props.insert("ColumnLineWidth".to_string(), "1,1".to_string());
+        let mut diagnostics = Diagnostics::lenient();
+        let layout = parse_column_layout(&props, 3, &mut diagnostics).unwrap();
+        assert_eq!(layout.column_spacing, vec![2, defaults::COLUMN_SPACING]);
+        assert_eq!(
+            layout.column_line_width,
+            vec![
+                1,
+                1,
+                defaults::COLUMN_LINE_WIDTH,
+                defaults::COLUMN_LINE_WIDTH
+            ]
+        );
+        let kinds: Vec<(String, DiagnosticKind)> = diagnostics
+            .into_vec()
+            .into_iter()
+            .map(|d| (d.key, d.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (
+                    "ColumnSpacing".to_string(),
+                    DiagnosticKind::ShortList {
+                        expected: 2,
+                        found: 1
+                    }
+                ),
+                (
+                    "ColumnLineWidth".to_string(),
+                    DiagnosticKind::ShortList {
+                        expected: 4,
+                        found: 2
+                    }
+                ),
+            ]
+        );
     }
 
     #[test]
@@ -506,7 +998,7 @@ mod tests {
         props.insert("ScorePosition".to_string(), "300".to_string());
         props.insert("ComboPosition".to_string(), "350".to_string());
 
-        let positions = parse_positions(&props);
+        let positions = parse_positions(&props, &mut Diagnostics::strict()).unwrap();
         assert_eq!(positions.hit_position, 400);
         assert_eq!(positions.light_position, 200);
         assert_eq!(positions.score_position, 300);
@@ -574,7 +1066,7 @@ mod tests {
         props.insert("ColourHold".to_string(), "255,230,0,255".to_string());
         props.insert("ColourBreak".to_string(), "255,0,0".to_string());
 
-        let colours = parse_colours(&props, 2);
+        let colours = parse_colours(&props, 2, &mut Diagnostics::strict()).unwrap();
         assert_eq!(colours.columns.len(), 2);
         assert_eq!(colours.lights.len(), 2);
         assert_eq!(colours.column_line, [255, 255, 255, 255]);
@@ -594,7 +1086,7 @@ mod tests {
         props.insert("UpsideDown".to_string(), "0".to_string());
         props.insert("ComboBurstStyle".to_string(), "2".to_string());
 
-        let behavior = parse_behavior(&props, 4);
+        let behavior = parse_behavior(&props, 4, &mut Diagnostics::strict()).unwrap();
         assert!(behavior.judgement_line);
         assert_eq!(behavior.light_frame_per_second, 60);
         assert!(!behavior.upside_down);
@@ -606,11 +1098,25 @@ mod tests {
         let mut props = Properties::new();
         props.insert("NoteBodyStyle".to_string(), "1".to_string());
         props.insert("NoteBodyStyle0".to_string(), "2".to_string());
-        props.insert("NoteBodyStyle1".to_string(), "3".to_string());
 
-        let style = parse_note_body_style(&props, 2);
+        let style = parse_note_body_style(&props, 3, &mut Diagnostics::strict()).unwrap();
         assert_eq!(style.global, 1);
-        assert_eq!(style.per_column, vec![2, 3]);
+        assert_eq!(style.per_column, vec![2, 1, 1]);
+
+        props.insert("NoteBodyStyle1".to_string(), "3".to_string());
+        let mut diagnostics = Diagnostics::lenient();
+        let style = parse_note_body_style(&props, 3, &mut diagnostics).unwrap();
+        assert_eq!(style.per_column, vec![2, 1, 1]);
+        let kinds: Vec<(String, DiagnosticKind)> = diagnostics
+            .into_vec()
+            .into_iter()
+            .map(|d| (d.key, d.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![("NoteBodyStyle1".to_string(), DiagnosticKind::InvalidValue)]
+        );
+        assert!(parse_note_body_style(&props, 3, &mut Diagnostics::strict()).is_err());
     }
 
     #[test]
@@ -623,13 +1129,12 @@ mod tests {
         props.insert("NoteFlip0".to_string(), "1".to_string());
         props.insert("NoteFlip1".to_string(), "0".to_string());
 
-        let config = parse_flip_config(&props, 2);
+        let config = parse_flip_config(&props, 2, &mut Diagnostics::strict()).unwrap();
         assert!(config.key_flip);
         assert!(config.note_flip);
         assert_eq!(config.per_column_key_flip, vec![true, false]);
     }
 
-
     #[test]
     fn test_parse_numbered_strings() {
         let mut props = Properties::new();
@@ -645,14 +1150,14 @@ mod tests {
         assert_eq!(strings_with_suffix, vec!["value0D", "value1D"]);
     }
 
-
     #[test]
     fn test_parse_numbered_values() {
         let mut props = Properties::new();
         props.insert("Test0".to_string(), "1".to_string());
         props.insert("Test1".to_string(), "2".to_string());
 
-        let values: Vec<i32> = parse_numbered_values(&props, "Test", 3, 0);
+        let values: Vec<i32> =
+            parse_numbered_values(&props, "Test", 3, 0, &mut Diagnostics::strict()).unwrap();
         assert_eq!(values, vec![1, 2, 0]);
     }
 
@@ -662,7 +1167,7 @@ mod tests {
         props.insert("Test0".to_string(), "1".to_string());
         props.insert("Test1".to_string(), "0".to_string());
 
-        let bools = parse_numbered_bools(&props, "Test", 3);
+        let bools = parse_numbered_bools(&props, "Test", 3, &mut Diagnostics::strict()).unwrap();
         assert_eq!(bools, vec![true, false, false]);
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,6 @@
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ManiaConfig {
     pub keys: Keys,
     pub special_style: SpecialStyle,
@@ -9,21 +11,28 @@ pub struct ManiaConfig {
     pub behavior: Behavior,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Keys {
     pub count: u32,
     pub keys_under_notes: bool,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SpecialStyle {
+    #[cfg_attr(feature = "schemars", schemars(range(min = 0, max = 2)))]
     pub style_type: u8, // 0, 1, or 2
     pub split_stages: bool,
     pub stage_separation: u32,
     pub separate_score: bool,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ColumnLayout {
     pub column_start: u32,
     pub column_right: u32,
@@ -35,13 +44,17 @@ pub struct ColumnLayout {
     pub width_for_note_height_scale: Option<u32>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct LightingWidth {
     pub n_width: Vec<u32>,
     pub l_width: Vec<u32>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Positions {
     pub hit_position: u32,
     pub light_position: u32,
@@ -49,19 +62,38 @@ pub struct Positions {
     pub combo_position: u32,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(rename = "ManiaColours"))]
 pub struct Colours {
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(inner(inner(range(min = 0, max = 255))))
+    )]
     pub columns: Vec<[u8; 4]>,
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(inner(inner(range(min = 0, max = 255))))
+    )]
     pub lights: Vec<[u8; 4]>,
+    #[cfg_attr(feature = "schemars", schemars(inner(range(min = 0, max = 255))))]
     pub column_line: [u8; 4],
+    #[cfg_attr(feature = "schemars", schemars(inner(range(min = 0, max = 255))))]
     pub barline: [u8; 4],
+    #[cfg_attr(feature = "schemars", schemars(inner(range(min = 0, max = 255))))]
     pub judgement_line: [u8; 3],
+    #[cfg_attr(feature = "schemars", schemars(inner(range(min = 0, max = 255))))]
     pub key_warning: [u8; 3],
+    #[cfg_attr(feature = "schemars", schemars(inner(range(min = 0, max = 255))))]
     pub hold: [u8; 4],
+    #[cfg_attr(feature = "schemars", schemars(inner(range(min = 0, max = 255))))]
     pub break_colour: [u8; 3],
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Images {
     pub keys: KeyImages,
     pub notes: NoteImages,
@@ -69,13 +101,17 @@ pub struct Images {
     pub hits: HitImages,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct KeyImages {
-    pub normal: Vec<String>,    // KeyImage#
-    pub pressed: Vec<String>,   // KeyImage#D
+    pub normal: Vec<String>,  // KeyImage#
+    pub pressed: Vec<String>, // KeyImage#D
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct NoteImages {
     pub regular: Vec<String>,   // NoteImage#
     pub hold_head: Vec<String>, // NoteImage#H
@@ -83,7 +119,9 @@ pub struct NoteImages {
     pub hold_tail: Vec<String>, // NoteImage#T
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct StageImages {
     pub left: String,
     pub right: String,
@@ -95,7 +133,9 @@ pub struct StageImages {
     pub warning_arrow: String,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct HitImages {
     pub hit_0: String,
     pub hit_50: String,
@@ -105,7 +145,9 @@ pub struct HitImages {
     pub hit_300g: String,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Behavior {
     pub judgement_line: bool,
     pub light_frame_per_second: u32,
@@ -115,13 +157,19 @@ pub struct Behavior {
     pub combo_burst_style: u8,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct NoteBodyStyle {
-    pub global: u8,             // 0, 1, or 2
+    #[cfg_attr(feature = "schemars", schemars(range(min = 0, max = 2)))]
+    pub global: u8, // 0, 1, or 2
+    #[cfg_attr(feature = "schemars", schemars(inner(range(min = 0, max = 2))))]
     pub per_column: Vec<u8>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct FlipConfig {
     pub key_flip: bool,
     pub note_flip: bool,
@@ -129,10 +177,12 @@ pub struct FlipConfig {
     pub per_column_note_flip: NoteFlipPerColumn,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct NoteFlipPerColumn {
     pub note: Vec<bool>,
     pub hold_head: Vec<bool>,
     pub hold_body: Vec<bool>,
     pub hold_tail: Vec<bool>,
-}
\ No newline at end of file
+}
@@ -0,0 +1,279 @@
+use crate::ini::Properties;
+use crate::mania::parser::{default_config, parse_images};
+use crate::mania::structs::{Images, ManiaConfig};
+use crate::writer::{flag, list, push, Entries, ToIni, WriteOptions};
+
+impl ToIni for ManiaConfig {
+    const SECTION: &'static str = "Mania";
+
+    fn entries(&self) -> Entries {
+        mania_entries(self)
+    }
+
+    /// Defaults depend on the key count, see `default_config`. Images are not defaults:
+    /// leaving one out is not the same as naming the default skin's image.
+    fn default_entries(&self) -> Entries {
+        default_config(self.keys.count)
+            .map(|config| {
+                mania_entries(&ManiaConfig {
+                    images: parse_images(&Properties::new(), self.keys.count),
+                    ..config
+                })
+            })
+            .unwrap_or_default()
+    }
+
+    /// `Keys` is always kept, osu! ignores a block without it.
+    fn entries_with(&self, options: WriteOptions) -> Entries {
+        let entries = self.entries();
+        if !options.omit_defaults {
+            return entries;
+        }
+        let defaults = self.default_entries();
+        entries
+            .into_iter()
+            .filter(|entry| entry.0 == "Keys" || !defaults.contains(entry))
+            .collect()
+    }
+}
+
+/// `Key: Value` lines of a `[Mania]` block.
+///
+/// Per-column keys use the game's numbering: `Colour#` and `ColourLight#` count from 1,
+/// every other family from 0. Empty images and lists are left out, like per-column flags
+/// and styles that are off.
+pub fn mania_entries(config: &ManiaConfig) -> Entries {
+    let layout = &config.column_layout;
+    let positions = &config.positions;
+    let special = &config.special_style;
+    let behavior = &config.behavior;
+    let flips = &behavior.flip_config;
+    let colours = &config.colours;
+    let images = &config.images;
+
+    let mut entries = Entries::new();
+    push(&mut entries, "Keys", config.keys.count);
+    push(&mut entries, "ColumnStart", layout.column_start);
+    push(&mut entries, "ColumnRight", layout.column_right);
+    push_list(&mut entries, "ColumnSpacing", &layout.column_spacing);
+    push_list(&mut entries, "ColumnWidth", &layout.column_width);
+    push_list(&mut entries, "ColumnLineWidth", &layout.column_line_width);
+    push(&mut entries, "BarlineHeight", layout.barline_height);
+    push_list(
+        &mut entries,
+        "LightingNWidth",
+        &layout.lighting_width.n_width,
+    );
+    push_list(
+        &mut entries,
+        "LightingLWidth",
+        &layout.lighting_width.l_width,
+    );
+    if let Some(width) = layout.width_for_note_height_scale {
+        push(&mut entries, "WidthForNoteHeightScale", width);
+    }
+
+    push(&mut entries, "HitPosition", positions.hit_position);
+    push(&mut entries, "LightPosition", positions.light_position);
+    push(&mut entries, "ScorePosition", positions.score_position);
+    push(&mut entries, "ComboPosition", positions.combo_position);
+
+    push(&mut entries, "JudgementLine", flag(behavior.judgement_line));
+    push(
+        &mut entries,
+        "LightFramePerSecond",
+        behavior.light_frame_per_second,
+    );
+    push(&mut entries, "SpecialStyle", special.style_type);
+    push(&mut entries, "ComboBurstStyle", behavior.combo_burst_style);
+    push(&mut entries, "SplitStages", flag(special.split_stages));
+    push(&mut entries, "StageSeparation", special.stage_separation);
+    push(&mut entries, "SeparateScore", flag(special.separate_score));
+    push(
+        &mut entries,
+        "KeysUnderNotes",
+        flag(config.keys.keys_under_notes),
+    );
+    push(&mut entries, "UpsideDown", flag(behavior.upside_down));
+
+    push(&mut entries, "KeyFlip", flag(flips.key_flip));
+    push(&mut entries, "NoteFlip", flag(flips.note_flip));
+    push_flags(&mut entries, "KeyFlip", "", &flips.per_column_key_flip);
+    let note_flips = &flips.per_column_note_flip;
+    push_flags(&mut entries, "NoteFlip", "", &note_flips.note);
+    push_flags(&mut entries, "NoteFlip", "H", &note_flips.hold_head);
+    push_flags(&mut entries, "NoteFlip", "L", &note_flips.hold_body);
+    push_flags(&mut entries, "NoteFlip", "T", &note_flips.hold_tail);
+
+    let body_style = &behavior.note_body_style;
+    push(&mut entries, "NoteBodyStyle", body_style.global);
+    // Columns without their own key take the global style when read back
+    for (column, style) in body_style.per_column.iter().enumerate() {
+        if *style != body_style.global {
+            push(&mut entries, &format!("NoteBodyStyle{}", column), style);
+        }
+    }
+
+    for (column, colour) in colours.columns.iter().enumerate() {
+        push(
+            &mut entries,
+            &format!("Colour{}", column + 1),
+            rgba(*colour),
+        );
+    }
+    for (column, colour) in colours.lights.iter().enumerate() {
+        push(
+            &mut entries,
+            &format!("ColourLight{}", column + 1),
+            rgba(*colour),
+        );
+    }
+    push(&mut entries, "ColourColumnLine", rgba(colours.column_line));
+    push(&mut entries, "ColourBarline", rgba(colours.barline));
+    push(
+        &mut entries,
+        "ColourJudgementLine",
+        list(&colours.judgement_line),
+    );
+    push(&mut entries, "ColourKeyWarning", list(&colours.key_warning));
+    push(&mut entries, "ColourHold", rgba(colours.hold));
+    push(&mut entries, "ColourBreak", list(&colours.break_colour));
+
+    entries.extend(image_entries(images));
+    entries
+}
+
+/// `Key: Value` lines of the images of a `[Mania]` block, leaving out the empty ones.
+pub fn image_entries(images: &Images) -> Entries {
+    let mut entries = Entries::new();
+    push_images(&mut entries, "KeyImage", "", &images.keys.normal);
+    push_images(&mut entries, "KeyImage", "D", &images.keys.pressed);
+    push_images(&mut entries, "NoteImage", "", &images.notes.regular);
+    push_images(&mut entries, "NoteImage", "H", &images.notes.hold_head);
+    push_images(&mut entries, "NoteImage", "L", &images.notes.hold_body);
+    push_images(&mut entries, "NoteImage", "T", &images.notes.hold_tail);
+
+    let stage = &images.stage;
+    let hits = &images.hits;
+    for (key, image) in [
+        ("StageLeft", &stage.left),
+        ("StageRight", &stage.right),
+        ("StageBottom", &stage.bottom),
+        ("StageHint", &stage.hint),
+        ("StageLight", &stage.light),
+        ("LightingN", &stage.lighting_n),
+        ("LightingL", &stage.lighting_l),
+        ("WarningArrow", &stage.warning_arrow),
+        ("Hit0", &hits.hit_0),
+        ("Hit50", &hits.hit_50),
+        ("Hit100", &hits.hit_100),
+        ("Hit200", &hits.hit_200),
+        ("Hit300", &hits.hit_300),
+        ("Hit300g", &hits.hit_300g),
+    ] {
+        if !image.is_empty() {
+            push(&mut entries, key, image);
+        }
+    }
+    entries
+}
+
+fn push_list(entries: &mut Entries, key: &str, values: &[u32]) {
+    if !values.is_empty() {
+        push(entries, key, list(values));
+    }
+}
+
+fn push_flags(entries: &mut Entries, prefix: &str, suffix: &str, values: &[bool]) {
+    for (column, value) in values.iter().enumerate() {
+        if *value {
+            push(entries, &format!("{}{}{}", prefix, column, suffix), "1");
+        }
+    }
+}
+
+fn push_images(entries: &mut Entries, prefix: &str, suffix: &str, images: &[String]) {
+    for (column, image) in images.iter().enumerate() {
+        if !image.is_empty() {
+            push(entries, &format!("{}{}{}", prefix, column, suffix), image);
+        }
+    }
+}
+
+/// Alpha is only written when the colour is not opaque.
+fn rgba(colour: [u8; 4]) -> String {
+    match colour {
+        [r, g, b, 255] => list(&[r, g, b]),
+        colour => list(&colour),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::parser::read_mania_config_from_str;
+
+    #[test]
+    fn test_mania_entries() {
+        let configs = read_mania_config_from_str(
+            "[Mania]\nKeys: 4\nColumnWidth: 40,40,40,40\nColour1: 0,0,0,128\nColour2: 10,10,10\n\
+             NoteFlip2H: 1\nNoteBodyStyle3: 2\nKeyImage1D: keys\\down\nHit300g: hits\\300g\n",
+        )
+        .unwrap();
+        let entries = mania_entries(&configs[0]);
+        let get = |key: &str| {
+            entries
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+        };
+
+        assert_eq!(entries[0], ("Keys".to_string(), "4".to_string()));
+        assert_eq!(get("ColumnWidth"), Some("40,40,40,40"));
+        assert_eq!(get("ColumnSpacing"), Some("0,0,0"));
+        assert_eq!(get("Colour1"), Some("0,0,0,128"));
+        assert_eq!(get("Colour2"), Some("10,10,10"));
+        assert_eq!(get("NoteFlip2H"), Some("1"));
+        assert_eq!(get("NoteFlip1H"), None);
+        assert_eq!(get("NoteBodyStyle3"), Some("2"));
+        assert_eq!(get("KeyImage1D"), Some("keys\\down"));
+        assert_eq!(get("KeyImage0D"), None);
+        assert_eq!(get("Hit300g"), Some("hits\\300g"));
+        assert_eq!(get("BarlineHeight"), Some("1"));
+    }
+
+    #[test]
+    fn test_omit_defaults() {
+        let configs = read_mania_config_from_str(
+            "[Mania]\nKeys: 7\nHitPosition: 420\nColumnWidth: 40,30,30,30,30,30,30\n",
+        )
+        .unwrap();
+        let options = WriteOptions {
+            omit_defaults: true,
+        };
+        let text = configs[0].to_ini_string_with(options);
+        assert_eq!(
+            text,
+            "[Mania]\nKeys: 7\nColumnWidth: 40,30,30,30,30,30,30\nHitPosition: 420\n\n"
+        );
+        assert_eq!(read_mania_config_from_str(&text).unwrap(), configs);
+    }
+
+    #[test]
+    fn test_note_body_style_round_trip() {
+        let configs = read_mania_config_from_str(
+            "[Mania]\nKeys: 4\nNoteBodyStyle: 1\nNoteBodyStyle0: 0\nNoteBodyStyle2: 1\n",
+        )
+        .unwrap();
+        assert_eq!(
+            configs[0].behavior.note_body_style.per_column,
+            vec![0, 1, 1, 1]
+        );
+
+        let text = configs[0].to_ini_string();
+        assert!(text.contains("NoteBodyStyle0: 0\n"));
+        assert!(!text.contains("NoteBodyStyle1"));
+        assert!(!text.contains("NoteBodyStyle2"));
+        assert_eq!(read_mania_config_from_str(&text).unwrap(), configs);
+    }
+}
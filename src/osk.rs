@@ -0,0 +1,445 @@
+use std::fs::File;
+use std::io::{Read, Seek, Write};
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, DateTime, ZipArchive, ZipWriter};
+
+use crate::assets::AssetStore;
+use crate::diagnostic::{Diagnostic, ParseMode};
+use crate::elements::{is_referenced, referenced_names};
+use crate::error::SkinError;
+use crate::parser::normalize_asset_path;
+use crate::skin::Skin;
+
+const SKIN_INI: &str = "skin.ini";
+
+/// Largest file, once decompressed, `OskArchive` reads from an archive.
+pub const MAX_ENTRY_SIZE: u64 = 64 * 1024 * 1024;
+/// Largest total of the decompressed files of an archive `OskArchive` reads.
+pub const MAX_ARCHIVE_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Decompressed sizes past which reading an archive fails, so that a forged header or a
+/// zip bomb cannot exhaust memory.
+#[derive(Clone, Copy, Debug)]
+struct ReadLimits {
+    entry: u64,
+    total: u64,
+}
+
+const LIMITS: ReadLimits = ReadLimits {
+    entry: MAX_ENTRY_SIZE,
+    total: MAX_ARCHIVE_SIZE,
+};
+
+/// A `.osk` skin archive read into memory: its parsed skin.ini and every other file.
+///
+/// Nothing is extracted to disk. When the whole skin sits in a single folder of the
+/// archive, paths are relative to that folder.
+#[derive(Debug)]
+pub struct OskArchive {
+    pub skin: Skin,
+    /// Every file except skin.ini, relative to the skin folder.
+    pub assets: AssetStore,
+    /// Folder of the archive holding the skin, empty for the root.
+    root: String,
+    /// Path of skin.ini in the archive, `None` when the skin has none.
+    ini_path: Option<String>,
+}
+
+impl OskArchive {
+    /// Reads the `.osk` at `path`, failing on the first invalid skin.ini entry.
+    pub fn open(path: &str) -> Result<Self, SkinError> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Reads the `.osk` at `path` the way osu! does, see `Skin::load_lenient`.
+    pub fn open_lenient(path: &str) -> Result<(Self, Vec<Diagnostic>), SkinError> {
+        Self::from_reader_lenient(File::open(path)?)
+    }
+
+    /// Reads a `.osk` from memory or any other seekable source.
+    pub fn from_reader(reader: impl Read + Seek) -> Result<Self, SkinError> {
+        Ok(Self::read(reader, ParseMode::Strict, LIMITS)?.0)
+    }
+
+    pub fn from_reader_lenient(
+        reader: impl Read + Seek,
+    ) -> Result<(Self, Vec<Diagnostic>), SkinError> {
+        Self::read(reader, ParseMode::Lenient, LIMITS)
+    }
+
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    pub fn ini_path(&self) -> Option<&str> {
+        self.ini_path.as_deref()
+    }
+
+    /// Packages the archive again, see `write_osk`. The skin ends up at the root.
+    pub fn write_to(
+        &self,
+        writer: impl Write + Seek,
+        options: OskWriteOptions,
+    ) -> Result<(), SkinError> {
+        write_osk(&self.skin, &self.assets, options, writer)
+    }
+
+    fn read(
+        reader: impl Read + Seek,
+        mode: ParseMode,
+        limits: ReadLimits,
+    ) -> Result<(Self, Vec<Diagnostic>), SkinError> {
+        let mut archive = ZipArchive::new(reader)?;
+
+        let mut files = Vec::new();
+        let mut total = 0;
+        for index in 0..archive.len() {
+            let entry = archive.by_index(index)?;
+            if entry.is_dir() {
+                continue;
+            }
+            let name = entry.name().to_string();
+            let too_large = |limit| SkinError::ArchiveTooLarge {
+                entry: Some(name.clone()),
+                limit,
+            };
+            // The declared size is only a hint, the read below is what is enforced
+            if entry.size() > limits.entry {
+                return Err(too_large(limits.entry));
+            }
+            let mut bytes = Vec::new();
+            entry.take(limits.entry + 1).read_to_end(&mut bytes)?;
+            if bytes.len() as u64 > limits.entry {
+                return Err(too_large(limits.entry));
+            }
+            total += bytes.len() as u64;
+            if total > limits.total {
+                return Err(SkinError::ArchiveTooLarge {
+                    entry: None,
+                    limit: limits.total,
+                });
+            }
+            files.push((normalize_asset_path(&name), bytes));
+        }
+
+        let ini_index = find_skin_ini(&files);
+        let root = match ini_index {
+            Some(index) => files[index]
+                .0
+                .rsplit_once('/')
+                .map(|(folder, _)| folder.to_string())
+                .unwrap_or_default(),
+            None => String::new(),
+        };
+
+        // Without a skin.ini, osu! still loads the skin with the default values
+        let (skin, diagnostics) = match ini_index {
+            Some(index) => Skin::parse_bytes(&files[index].1, Some(&files[index].0), mode)?,
+            None => Skin::parse_bytes(&[], None, mode)?,
+        };
+        let ini_path = ini_index.map(|index| files[index].0.clone());
+
+        let mut assets = AssetStore::new();
+        for (index, (path, bytes)) in files.into_iter().enumerate() {
+            if Some(index) == ini_index {
+                continue;
+            }
+            let relative = match root.as_str() {
+                "" => Some(path.as_str()),
+                root => path
+                    .strip_prefix(root)
+                    .and_then(|rest| rest.strip_prefix('/')),
+            };
+            // Files outside the skin folder are not part of the skin
+            if let Some(relative) = relative {
+                assets.insert(relative, bytes);
+            }
+        }
+
+        let archive = Self {
+            skin,
+            assets,
+            root,
+            ini_path,
+        };
+        Ok((archive, diagnostics))
+    }
+}
+
+/// How `write_osk` packages a skin.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OskWriteOptions {
+    /// Leave out the files osu! would never load, see `elements::is_referenced`.
+    pub remove_unreferenced: bool,
+}
+
+/// Packages `skin` and `assets` as a `.osk`, with skin.ini regenerated from `skin`.
+///
+/// The output only depends on its inputs: skin.ini comes first, then the files sorted by
+/// path, all with the same timestamp, permissions and compression.
+pub fn write_osk(
+    skin: &Skin,
+    assets: &AssetStore,
+    options: OskWriteOptions,
+    writer: impl Write + Seek,
+) -> Result<(), SkinError> {
+    let referenced = referenced_names(skin);
+    let file_options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .last_modified_time(DateTime::default())
+        .unix_permissions(0o644);
+
+    let mut zip = ZipWriter::new(writer);
+    zip.start_file(SKIN_INI, file_options)?;
+    zip.write_all(skin.to_ini_string().as_bytes())?;
+    for (path, bytes) in assets.iter() {
+        if options.remove_unreferenced && !is_referenced(&referenced, path) {
+            continue;
+        }
+        zip.start_file(path, file_options)?;
+        zip.write_all(bytes)?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+/// skin.ini at the root of the archive, or in its only top-level folder.
+fn find_skin_ini(files: &[(String, Vec<u8>)]) -> Option<usize> {
+    let is_ini = |name: &str| name.eq_ignore_ascii_case(SKIN_INI);
+
+    if let Some(index) = files.iter().position(|(path, _)| is_ini(path)) {
+        return Some(index);
+    }
+    let mut nested = files
+        .iter()
+        .enumerate()
+        .filter(|(_, (path, _))| matches!(path.split_once('/'), Some((_, name)) if is_ini(name)));
+    match (nested.next(), nested.next()) {
+        (Some((index, _)), None) => Some(index),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn osk(files: &[(&str, &[u8])]) -> Cursor<Vec<u8>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, bytes) in files {
+            if name.ends_with('/') {
+                writer
+                    .add_directory(*name, SimpleFileOptions::default())
+                    .unwrap();
+            } else {
+                writer
+                    .start_file(*name, SimpleFileOptions::default())
+                    .unwrap();
+                writer.write_all(bytes).unwrap();
+            }
+        }
+        let mut cursor = writer.finish().unwrap();
+        cursor.set_position(0);
+        cursor
+    }
+
+    #[test]
+    fn test_read_osk() -> Result<(), SkinError> {
+        let ini = std::fs::read("./resources/test.ini")?;
+        let archive = OskArchive::from_reader(osk(&[
+            ("Skin.INI", &ini),
+            ("Arrownote/", b""),
+            ("Arrownote\\holdbody.png", b"png"),
+            ("mania-key1.png", b"key"),
+        ]))?;
+
+        assert_eq!(archive.skin.general.metadata.name, "Aggro");
+        assert_eq!(archive.ini_path(), Some("Skin.INI"));
+        assert_eq!(archive.root(), "");
+        assert_eq!(
+            archive.assets.paths().collect::<Vec<_>>(),
+            vec!["Arrownote/holdbody.png", "mania-key1.png"]
+        );
+        assert_eq!(
+            archive.assets.find("ARROWNOTE\\HoldBody.png"),
+            Some(("Arrownote/holdbody.png", &b"png"[..]))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_osk_nested_folder() -> Result<(), SkinError> {
+        let archive = OskArchive::from_reader(osk(&[
+            ("My Skin/skin.ini", b"[General]\nName: Nested\n"),
+            ("My Skin/mania/mania-hit0.png", b"hit"),
+            ("readme.txt", b"not part of the skin"),
+        ]))?;
+        assert_eq!(archive.skin.general.metadata.name, "Nested");
+        assert_eq!(archive.root(), "My Skin");
+        assert_eq!(
+            archive.assets.paths().collect::<Vec<_>>(),
+            vec!["mania/mania-hit0.png"]
+        );
+        assert_eq!(
+            archive.skin.locate("General", "Name").unwrap().file,
+            Some("My Skin/skin.ini".into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_osk_without_skin_ini() -> Result<(), SkinError> {
+        let archive = OskArchive::from_reader(osk(&[
+            ("a/skin.ini", b"[General]\nName: A\n"),
+            ("b/skin.ini", b"[General]\nName: B\n"),
+        ]))?;
+        // Two candidates, none of them is the skin's
+        assert_eq!(archive.ini_path(), None);
+        assert_eq!(archive.skin.general.metadata.name, "Unknown");
+        assert_eq!(archive.assets.len(), 2);
+        Ok(())
+    }
+
+    /// Overwrites the uncompressed size every header of `zip` declares.
+    fn forge_size(zip: &mut [u8], size: u32) {
+        for offset in 0..zip.len().saturating_sub(4) {
+            let field = match &zip[offset..offset + 4] {
+                [0x50, 0x4b, 0x03, 0x04] => offset + 22,
+                [0x50, 0x4b, 0x01, 0x02] => offset + 24,
+                _ => continue,
+            };
+            zip[field..field + 4].copy_from_slice(&size.to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn test_read_osk_limits() {
+        let mut forged = osk(&[("hitcircle.png", b"circle")]).into_inner();
+        forge_size(&mut forged, 0xFFFF_FFF0);
+        match OskArchive::from_reader(Cursor::new(forged)) {
+            Err(SkinError::ArchiveTooLarge { entry, limit }) => {
+                assert_eq!(entry.as_deref(), Some("hitcircle.png"));
+                assert_eq!(limit, MAX_ENTRY_SIZE);
+            }
+            other => panic!("expected an oversized entry, got {:?}", other),
+        }
+
+        let files = osk(&[
+            ("a.png", b"12345"),
+            ("b.png", b"12345"),
+            ("c.png", b"12345"),
+        ]);
+        let limits = ReadLimits {
+            entry: 5,
+            total: 12,
+        };
+        assert!(matches!(
+            OskArchive::read(files, ParseMode::Strict, limits),
+            Err(SkinError::ArchiveTooLarge {
+                entry: None,
+                limit: 12
+            })
+        ));
+        let limits = ReadLimits {
+            entry: 4,
+            total: 12,
+        };
+        assert!(matches!(
+            OskArchive::read(osk(&[("a.png", b"12345")]), ParseMode::Strict, limits),
+            Err(SkinError::ArchiveTooLarge {
+                entry: Some(_),
+                limit: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn test_write_osk() -> Result<(), SkinError> {
+        let ini = std::fs::read("./resources/test.ini")?;
+        let archive = OskArchive::from_reader(osk(&[
+            ("Aggro/skin.ini", &ini),
+            ("Aggro/Arrownote/holdbody@2x.png", b"body"),
+            ("Aggro/hitcircle.png", b"circle"),
+            ("Aggro/notes.txt", b"unused"),
+        ]))?;
+
+        let mut full = Cursor::new(Vec::new());
+        archive.write_to(&mut full, OskWriteOptions::default())?;
+        let mut again = Cursor::new(Vec::new());
+        archive.write_to(&mut again, OskWriteOptions::default())?;
+        // Same input, same bytes
+        assert_eq!(full.get_ref(), again.get_ref());
+
+        full.set_position(0);
+        let written = OskArchive::from_reader(full)?;
+        assert_eq!(written.ini_path(), Some("skin.ini"));
+        assert_eq!(written.skin.general.metadata.name, "Aggro");
+        assert_eq!(written.assets, archive.assets);
+
+        let mut pruned = Cursor::new(Vec::new());
+        let options = OskWriteOptions {
+            remove_unreferenced: true,
+        };
+        archive.write_to(&mut pruned, options)?;
+        pruned.set_position(0);
+        let pruned = OskArchive::from_reader(pruned)?;
+        assert_eq!(
+            pruned.assets.paths().collect::<Vec<_>>(),
+            vec!["Arrownote/holdbody@2x.png", "hitcircle.png"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_osk_keeps_loaded_files() -> Result<(), SkinError> {
+        let ini = std::fs::read("./resources/test.ini")?;
+        let loaded = [
+            "comboburst-0.wav",
+            "multi-skipped.wav",
+            "pause-back-click.wav",
+            "pause-continue-click.wav",
+            "pause-hover.wav",
+            "pause-loop.wav",
+            "pause-retry-click.wav",
+            "target.png",
+            "targetoverlay@2x.png",
+        ];
+        let mut files: Vec<(&str, &[u8])> = vec![("skin.ini", &ini), ("readme.txt", b"unused")];
+        files.extend(loaded.iter().map(|path| (*path, b"file".as_slice())));
+        let archive = OskArchive::from_reader(osk(&files))?;
+
+        let mut pruned = Cursor::new(Vec::new());
+        let options = OskWriteOptions {
+            remove_unreferenced: true,
+        };
+        archive.write_to(&mut pruned, options)?;
+        pruned.set_position(0);
+        let pruned = OskArchive::from_reader(pruned)?;
+        assert_eq!(pruned.assets.paths().collect::<Vec<_>>(), loaded);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_osk_errors() {
+        let ini = std::fs::read("./resources/test2.ini").unwrap();
+        assert!(matches!(
+            OskArchive::from_reader(osk(&[("skin.ini", &ini)])),
+            Err(SkinError::InvalidValue { .. })
+        ));
+        let (archive, diagnostics) =
+            OskArchive::from_reader_lenient(osk(&[("skin.ini", &ini)])).unwrap();
+        assert_eq!(archive.skin.mania.len(), 5);
+        assert_eq!(diagnostics.len(), 5);
+
+        assert!(matches!(
+            OskArchive::from_reader(Cursor::new(b"not a zip".to_vec())),
+            Err(SkinError::Archive(_))
+        ));
+        assert!(matches!(
+            OskArchive::open("./resources/missing.osk"),
+            Err(SkinError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,764 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use crate::diagnostic::{Diagnostic, DiagnosticKind, Diagnostics};
+use crate::error::SkinError;
+use crate::ini::{Ini, Properties};
+use crate::mania::parser::{parse_color_rgb, parse_number_list};
+use crate::structs::{
+    CatchTheBeat, Colours, ComboColours, CursorConfig, FontConfig, Fonts, GameplayColours,
+    GameplayConfig, General, HyperDashColors, InterfaceColours, RgbColor, SkinMetadata,
+    SpinnerConfig,
+};
+
+const GENERAL_KEYS: &[&str] = &[
+    "Name",
+    "Author",
+    "Version",
+    "AnimationFramerate",
+    "AllowSliderBallTint",
+    "ComboBurstRandom",
+    "CursorCentre",
+    "CursorExpand",
+    "CursorRotate",
+    "CursorTrailRotate",
+    "CustomComboBurstSounds",
+    "HitCircleOverlayAboveNumber",
+    "HitCircleOverlayAboveNumer",
+    "LayeredHitSounds",
+    "SliderBallFlip",
+    "SliderStyle",
+    "SpinnerFadePlayfield",
+    "SpinnerFrequencyModulate",
+    "SpinnerNoBlink",
+];
+
+const COLOURS_KEYS: &[&str] = &[
+    "InputOverlayText",
+    "MenuGlow",
+    "SliderBall",
+    "SliderBorder",
+    "SliderTrackOverride",
+    "SongSelectActiveText",
+    "SongSelectInactiveText",
+    "SpinnerBackground",
+    "StarBreakAdditive",
+];
+
+const FONTS_KEYS: &[&str] = &[
+    "HitCirclePrefix",
+    "HitCircleOverlap",
+    "ScorePrefix",
+    "ScoreOverlap",
+    "ComboPrefix",
+    "ComboOverlap",
+];
+
+const CATCH_THE_BEAT_KEYS: &[&str] = &["HyperDash", "HyperDashFruit", "HyperDashAfterImage"];
+
+pub fn read_general(ini: &Ini, diagnostics: &mut Diagnostics) -> Result<General, SkinError> {
+    diagnostics.enter_section(0);
+    match ini.section("General") {
+        Some(props) => parse_general(props, diagnostics),
+        None => Ok(General::default()),
+    }
+}
+
+pub fn parse_general(
+    props: &Properties,
+    diagnostics: &mut Diagnostics,
+) -> Result<General, SkinError> {
+    check_keys(
+        props,
+        "General",
+        |key| GENERAL_KEYS.contains(&key),
+        diagnostics,
+    )?;
+    Ok(General {
+        metadata: parse_metadata(props, diagnostics)?,
+        cursor: parse_cursor(props, diagnostics)?,
+        spinner: parse_spinner(props, diagnostics)?,
+        gameplay: parse_gameplay(props, diagnostics)?,
+    })
+}
+
+pub fn parse_metadata(
+    props: &Properties,
+    diagnostics: &mut Diagnostics,
+) -> Result<SkinMetadata, SkinError> {
+    let default = SkinMetadata::default();
+    Ok(SkinMetadata {
+        name: get_last(props, "Name")
+            .map(String::from)
+            .unwrap_or(default.name),
+        author: get_last(props, "Author")
+            .map(String::from)
+            .unwrap_or(default.author),
+        version: get_last(props, "Version")
+            .map(String::from)
+            .unwrap_or(default.version),
+        animation_framerate: get_value(props, "General", "AnimationFramerate", diagnostics)?
+            .unwrap_or(default.animation_framerate),
+    })
+}
+
+pub fn parse_cursor(
+    props: &Properties,
+    diagnostics: &mut Diagnostics,
+) -> Result<CursorConfig, SkinError> {
+    let default = CursorConfig::default();
+    Ok(CursorConfig {
+        centre: get_flag(props, "General", "CursorCentre", diagnostics)?.unwrap_or(default.centre),
+        expand: get_flag(props, "General", "CursorExpand", diagnostics)?.unwrap_or(default.expand),
+        rotate: get_flag(props, "General", "CursorRotate", diagnostics)?.unwrap_or(default.rotate),
+        trail_rotate: get_flag(props, "General", "CursorTrailRotate", diagnostics)?
+            .unwrap_or(default.trail_rotate),
+    })
+}
+
+pub fn parse_spinner(
+    props: &Properties,
+    diagnostics: &mut Diagnostics,
+) -> Result<SpinnerConfig, SkinError> {
+    let default = SpinnerConfig::default();
+    Ok(SpinnerConfig {
+        fade_playfield: get_flag(props, "General", "SpinnerFadePlayfield", diagnostics)?
+            .unwrap_or(default.fade_playfield),
+        frequency_modulate: get_flag(props, "General", "SpinnerFrequencyModulate", diagnostics)?
+            .unwrap_or(default.frequency_modulate),
+        no_blink: get_flag(props, "General", "SpinnerNoBlink", diagnostics)?
+            .unwrap_or(default.no_blink),
+    })
+}
+
+pub fn parse_gameplay(
+    props: &Properties,
+    diagnostics: &mut Diagnostics,
+) -> Result<GameplayConfig, SkinError> {
+    let default = GameplayConfig::default();
+    Ok(GameplayConfig {
+        allow_slider_ball_tint: get_flag(props, "General", "AllowSliderBallTint", diagnostics)?
+            .unwrap_or(default.allow_slider_ball_tint),
+        combo_burst_random: get_flag(props, "General", "ComboBurstRandom", diagnostics)?
+            .unwrap_or(default.combo_burst_random),
+        custom_combo_burst_sounds: get_with(
+            props,
+            "General",
+            "CustomComboBurstSounds",
+            parse_number_list,
+            diagnostics,
+        )?
+        .unwrap_or(default.custom_combo_burst_sounds),
+        // osu! still accepts the misspelt key written by old skin editors
+        hit_circle_overlay_above_number: match get_flag(
+            props,
+            "General",
+            "HitCircleOverlayAboveNumber",
+            diagnostics,
+        )? {
+            Some(value) => value,
+            None => get_flag(props, "General", "HitCircleOverlayAboveNumer", diagnostics)?
+                .unwrap_or(default.hit_circle_overlay_above_number),
+        },
+        layered_hit_sounds: get_flag(props, "General", "LayeredHitSounds", diagnostics)?
+            .unwrap_or(default.layered_hit_sounds),
+        slider_ball_flip: get_flag(props, "General", "SliderBallFlip", diagnostics)?
+            .unwrap_or(default.slider_ball_flip),
+    })
+}
+
+pub fn read_colours(ini: &Ini, diagnostics: &mut Diagnostics) -> Result<Colours, SkinError> {
+    diagnostics.enter_section(0);
+    match ini.section("Colours") {
+        Some(props) => parse_colours(props, diagnostics),
+        None => Ok(Colours::default()),
+    }
+}
+
+pub fn parse_colours(
+    props: &Properties,
+    diagnostics: &mut Diagnostics,
+) -> Result<Colours, SkinError> {
+    check_keys(
+        props,
+        "Colours",
+        |key| COLOURS_KEYS.contains(&key) || combo_number(key).is_some(),
+        diagnostics,
+    )?;
+    Ok(Colours {
+        combo: parse_combo_colours(props, diagnostics)?,
+        interface: parse_interface_colours(props, diagnostics)?,
+        gameplay: parse_gameplay_colours(props, diagnostics)?,
+    })
+}
+
+/// Reads every `ComboN` key. The default colours are only used when the skin
+/// defines none at all, otherwise they are replaced as a whole.
+pub fn parse_combo_colours(
+    props: &Properties,
+    diagnostics: &mut Diagnostics,
+) -> Result<ComboColours, SkinError> {
+    let mut colours = BTreeMap::new();
+    for (key, _) in props.iter() {
+        if let Some(n) = combo_number(key) {
+            if let Some(colour) = get_rgb(props, "Colours", key, diagnostics)? {
+                colours.insert(n, colour);
+            }
+        }
+    }
+
+    if colours.is_empty() {
+        Ok(ComboColours::default())
+    } else {
+        Ok(ComboColours { colours })
+    }
+}
+
+pub fn parse_interface_colours(
+    props: &Properties,
+    diagnostics: &mut Diagnostics,
+) -> Result<InterfaceColours, SkinError> {
+    let default = InterfaceColours::default();
+    Ok(InterfaceColours {
+        menu_glow: get_rgb(props, "Colours", "MenuGlow", diagnostics)?.unwrap_or(default.menu_glow),
+        song_select_active_text: get_rgb(props, "Colours", "SongSelectActiveText", diagnostics)?
+            .unwrap_or(default.song_select_active_text),
+        song_select_inactive_text: get_rgb(
+            props,
+            "Colours",
+            "SongSelectInactiveText",
+            diagnostics,
+        )?
+        .unwrap_or(default.song_select_inactive_text),
+        input_overlay_text: get_rgb(props, "Colours", "InputOverlayText", diagnostics)?
+            .unwrap_or(default.input_overlay_text),
+    })
+}
+
+pub fn parse_gameplay_colours(
+    props: &Properties,
+    diagnostics: &mut Diagnostics,
+) -> Result<GameplayColours, SkinError> {
+    let default = GameplayColours::default();
+    Ok(GameplayColours {
+        slider_ball: get_rgb(props, "Colours", "SliderBall", diagnostics)?
+            .unwrap_or(default.slider_ball),
+        slider_border: get_rgb(props, "Colours", "SliderBorder", diagnostics)?
+            .unwrap_or(default.slider_border),
+        slider_track_override: get_rgb(props, "Colours", "SliderTrackOverride", diagnostics)?,
+        spinner_background: get_rgb(props, "Colours", "SpinnerBackground", diagnostics)?
+            .unwrap_or(default.spinner_background),
+        star_break_additive: get_rgb(props, "Colours", "StarBreakAdditive", diagnostics)?
+            .unwrap_or(default.star_break_additive),
+    })
+}
+
+pub fn read_fonts(ini: &Ini, diagnostics: &mut Diagnostics) -> Result<Fonts, SkinError> {
+    diagnostics.enter_section(0);
+    match ini.section("Fonts") {
+        Some(props) => parse_fonts(props, diagnostics),
+        None => Ok(Fonts::default()),
+    }
+}
+
+pub fn parse_fonts(props: &Properties, diagnostics: &mut Diagnostics) -> Result<Fonts, SkinError> {
+    check_keys(props, "Fonts", |key| FONTS_KEYS.contains(&key), diagnostics)?;
+    let default = Fonts::default();
+    Ok(Fonts {
+        hit_circle: parse_font(props, "HitCircle", default.hit_circle, diagnostics)?,
+        score: parse_font(props, "Score", default.score, diagnostics)?,
+        combo: parse_font(props, "Combo", default.combo, diagnostics)?,
+    })
+}
+
+/// Reads `<name>Prefix` and `<name>Overlap`.
+pub fn parse_font(
+    props: &Properties,
+    name: &str,
+    default: FontConfig,
+    diagnostics: &mut Diagnostics,
+) -> Result<FontConfig, SkinError> {
+    Ok(FontConfig {
+        prefix: get_last(props, &format!("{}Prefix", name))
+            .map(normalize_asset_path)
+            .filter(|prefix| !prefix.is_empty())
+            .unwrap_or(default.prefix),
+        overlap: get_value(props, "Fonts", &format!("{}Overlap", name), diagnostics)?
+            .unwrap_or(default.overlap),
+    })
+}
+
+pub fn read_catch_the_beat(
+    ini: &Ini,
+    diagnostics: &mut Diagnostics,
+) -> Result<CatchTheBeat, SkinError> {
+    diagnostics.enter_section(0);
+    match ini.section("CatchTheBeat") {
+        Some(props) => parse_catch_the_beat(props, diagnostics),
+        None => Ok(CatchTheBeat::default()),
+    }
+}
+
+pub fn parse_catch_the_beat(
+    props: &Properties,
+    diagnostics: &mut Diagnostics,
+) -> Result<CatchTheBeat, SkinError> {
+    check_keys(
+        props,
+        "CatchTheBeat",
+        |key| CATCH_THE_BEAT_KEYS.contains(&key),
+        diagnostics,
+    )?;
+    Ok(CatchTheBeat {
+        hyper_dash_colors: parse_hyper_dash_colors(props, diagnostics)?,
+    })
+}
+
+/// `HyperDashFruit` and `HyperDashAfterImage` stay `None` when absent so
+/// that they keep following `HyperDash`.
+pub fn parse_hyper_dash_colors(
+    props: &Properties,
+    diagnostics: &mut Diagnostics,
+) -> Result<HyperDashColors, SkinError> {
+    Ok(HyperDashColors {
+        main: get_rgb(props, "CatchTheBeat", "HyperDash", diagnostics)?
+            .unwrap_or(HyperDashColors::default().main),
+        fruit: get_rgb(props, "CatchTheBeat", "HyperDashFruit", diagnostics)?,
+        after_image: get_rgb(props, "CatchTheBeat", "HyperDashAfterImage", diagnostics)?,
+    })
+}
+
+/// Turns a skin-relative path as written in skin.ini (`fonts\score`,
+/// `./mania//hit0`) into a `/`-separated path without empty components.
+pub fn normalize_asset_path(path: &str) -> String {
+    path.trim()
+        .split(['\\', '/'])
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// `N` of a `ComboN` key, combo colours are numbered from 1.
+fn combo_number(key: &str) -> Option<u32> {
+    key.strip_prefix("Combo")?
+        .parse::<u32>()
+        .ok()
+        .filter(|n| *n > 0)
+}
+
+fn get_rgb(
+    props: &Properties,
+    section: &str,
+    key: &str,
+    diagnostics: &mut Diagnostics,
+) -> Result<Option<RgbColor>, SkinError> {
+    get_colour(props, section, key, parse_color_rgb, diagnostics)
+        .map(|colour| colour.map(RgbColor::from))
+}
+
+/// Value of the last `key` in the section, osu! lets later lines override earlier ones.
+pub(crate) fn get_last<'a>(props: &'a Properties, key: &str) -> Option<&'a str> {
+    props.get_last(key)
+}
+
+/// Reads `key` through `parse`, `Ok(None)` when the key is absent or,
+/// in lenient mode, when its value is invalid.
+pub(crate) fn get_with<T>(
+    props: &Properties,
+    section: &str,
+    key: &str,
+    parse: impl FnOnce(&str) -> Option<T>,
+    diagnostics: &mut Diagnostics,
+) -> Result<Option<T>, SkinError> {
+    let Some(value) = get_last(props, key) else {
+        return Ok(None);
+    };
+    match parse(value) {
+        Some(parsed) => Ok(Some(parsed)),
+        None => {
+            diagnostics.report(Diagnostic::new(
+                section,
+                key,
+                value,
+                DiagnosticKind::InvalidValue,
+            ))?;
+            Ok(None)
+        }
+    }
+}
+
+pub(crate) fn get_value<T: FromStr>(
+    props: &Properties,
+    section: &str,
+    key: &str,
+    diagnostics: &mut Diagnostics,
+) -> Result<Option<T>, SkinError> {
+    get_with(props, section, key, |v| v.parse().ok(), diagnostics)
+}
+
+/// osu! flags are integers, anything but 0 is true.
+pub(crate) fn get_flag(
+    props: &Properties,
+    section: &str,
+    key: &str,
+    diagnostics: &mut Diagnostics,
+) -> Result<Option<bool>, SkinError> {
+    get_with(
+        props,
+        section,
+        key,
+        |v| v.parse::<u32>().ok().map(|v| v != 0),
+        diagnostics,
+    )
+}
+
+/// Like `get_with`, but tells colours missing a component apart from other invalid values.
+pub(crate) fn get_colour<T>(
+    props: &Properties,
+    section: &str,
+    key: &str,
+    parse: impl FnOnce(&str) -> Option<T>,
+    diagnostics: &mut Diagnostics,
+) -> Result<Option<T>, SkinError> {
+    let Some(value) = get_last(props, key) else {
+        return Ok(None);
+    };
+    if value.split(',').count() < 3 {
+        diagnostics.report(Diagnostic::new(
+            section,
+            key,
+            value,
+            DiagnosticKind::IncompleteColour,
+        ))?;
+        return Ok(None);
+    }
+    get_with(props, section, key, parse, diagnostics)
+}
+
+/// Reports keys `is_known` rejects and keys written more than once.
+pub(crate) fn check_keys(
+    props: &Properties,
+    section: &str,
+    is_known: impl Fn(&str) -> bool,
+    diagnostics: &mut Diagnostics,
+) -> Result<(), SkinError> {
+    let mut seen = Vec::new();
+    for (key, value) in props.iter() {
+        if !is_known(key) {
+            diagnostics.report(Diagnostic::new(
+                section,
+                key,
+                value,
+                DiagnosticKind::UnknownKey,
+            ))?;
+        } else if seen.contains(&key) {
+            diagnostics.report(Diagnostic::new(
+                section,
+                key,
+                value,
+                DiagnosticKind::DuplicateKey,
+            ))?;
+        } else {
+            seen.push(key);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::parser::open_ini;
+    use std::error::Error;
+
+    #[test]
+    fn test_read_general() -> Result<(), Box<dyn Error>> {
+        let ini = open_ini("./resources/test2.ini")?;
+        let general = read_general(&ini, &mut Diagnostics::strict())?;
+
+        assert_eq!(general.metadata.name, "YUGEN REMASTERED v1.2");
+        assert_eq!(general.metadata.author, "Garin");
+        assert_eq!(general.metadata.version, "2.5");
+        assert_eq!(general.metadata.animation_framerate, -1);
+
+        assert!(general.cursor.centre);
+        assert!(!general.cursor.expand);
+        assert!(!general.cursor.rotate);
+        assert!(!general.cursor.trail_rotate);
+
+        assert!(!general.spinner.fade_playfield);
+        assert!(general.spinner.frequency_modulate);
+        assert!(!general.spinner.no_blink);
+
+        assert!(general.gameplay.allow_slider_ball_tint);
+        assert!(general.gameplay.slider_ball_flip);
+        assert!(general.gameplay.layered_hit_sounds);
+        assert!(general.gameplay.hit_circle_overlay_above_number);
+        assert!(general.gameplay.custom_combo_burst_sounds.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_general_defaults() -> Result<(), Box<dyn Error>> {
+        let ini = open_ini("./resources/test.ini")?;
+        let general = read_general(&ini, &mut Diagnostics::strict())?;
+
+        assert_eq!(general.metadata.name, "Aggro");
+        assert!(general.cursor.centre);
+        assert!(general.cursor.expand);
+        assert!(general.cursor.rotate);
+        assert!(general.cursor.trail_rotate);
+        assert!(!general.gameplay.allow_slider_ball_tint);
+        assert!(general.gameplay.slider_ball_flip);
+
+        let general = read_general(&Ini::new(), &mut Diagnostics::strict())?;
+        assert_eq!(general.metadata.name, "Unknown");
+        assert_eq!(general.metadata.version, "latest");
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_colours() -> Result<(), Box<dyn Error>> {
+        let ini = open_ini("./resources/test2.ini")?;
+        let colours = read_colours(&ini, &mut Diagnostics::strict())?;
+
+        assert_eq!(
+            colours.combo.ordered(),
+            vec![
+                RgbColor::new(161, 33, 236),
+                RgbColor::new(37, 185, 239),
+                RgbColor::new(23, 209, 116),
+                RgbColor::new(226, 45, 124),
+            ]
+        );
+        assert_eq!(
+            colours.interface.input_overlay_text,
+            RgbColor::new(250, 250, 250)
+        );
+        assert_eq!(
+            colours.interface.song_select_active_text,
+            RgbColor::new(250, 250, 250)
+        );
+        assert_eq!(
+            colours.interface.song_select_inactive_text,
+            RgbColor::new(230, 230, 230)
+        );
+        assert_eq!(colours.interface.menu_glow, RgbColor::new(0, 78, 155));
+        assert_eq!(colours.gameplay.slider_border, RgbColor::new(65, 65, 65));
+        assert_eq!(
+            colours.gameplay.slider_track_override,
+            Some(RgbColor::new(0, 0, 2))
+        );
+        assert_eq!(colours.gameplay.slider_ball, RgbColor::new(2, 170, 255));
+
+        let colours = read_colours(
+            &open_ini("./resources/test.ini")?,
+            &mut Diagnostics::strict(),
+        )?;
+        assert_eq!(colours.combo.ordered(), ComboColours::default().ordered());
+        assert_eq!(colours.gameplay.slider_track_override, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_combo_colours_with_gaps() {
+        let mut props = Properties::new();
+        props.insert("Combo3".to_string(), "0,0,255".to_string());
+        props.insert("Combo1".to_string(), "255,0,0".to_string());
+        props.insert("Combo12".to_string(), "0,255,0,128".to_string());
+
+        let combo = parse_combo_colours(&props, &mut Diagnostics::strict()).unwrap();
+        assert_eq!(
+            combo.colours.keys().copied().collect::<Vec<_>>(),
+            [1, 3, 12]
+        );
+        assert_eq!(combo.get(2), None);
+        assert_eq!(combo.get(12), Some(RgbColor::new(0, 255, 0)));
+        assert_eq!(combo.for_combo(0), Some(RgbColor::new(255, 0, 0)));
+        assert_eq!(combo.for_combo(1), Some(RgbColor::new(0, 0, 255)));
+        assert_eq!(combo.for_combo(3), Some(RgbColor::new(255, 0, 0)));
+
+        props.insert("Combo2".to_string(), "255,0".to_string());
+        assert!(matches!(
+            parse_combo_colours(&props, &mut Diagnostics::strict()),
+            Err(SkinError::InvalidValue { key, value, .. }) if key == "Combo2" && value == "255,0"
+        ));
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let mut props = Properties::new();
+        props.insert("Combo0".to_string(), "1,1,1".to_string());
+        assert!(matches!(
+            parse_colours(&props, &mut Diagnostics::strict()),
+            Err(SkinError::UnknownKey { section, key, .. }) if section == "Colours" && key == "Combo0"
+        ));
+
+        let mut props = Properties::new();
+        props.insert("Name".to_string(), "x".to_string());
+        props.insert("CursorCenter".to_string(), "1".to_string());
+        assert!(matches!(
+            parse_general(&props, &mut Diagnostics::strict()),
+            Err(SkinError::UnknownKey { key, .. }) if key == "CursorCenter"
+        ));
+    }
+
+    #[test]
+    fn test_read_fonts() -> Result<(), Box<dyn Error>> {
+        let fonts = read_fonts(
+            &open_ini("./resources/test2.ini")?,
+            &mut Diagnostics::strict(),
+        )?;
+        assert_eq!(fonts.hit_circle.prefix, "default");
+        assert_eq!(fonts.hit_circle.overlap, 8);
+        assert_eq!(fonts.score.prefix, "score");
+        assert_eq!(fonts.score.overlap, 10);
+        assert_eq!(fonts.combo.prefix, "combo");
+        assert_eq!(fonts.combo.overlap, 10);
+
+        let fonts = read_fonts(
+            &open_ini("./resources/test.ini")?,
+            &mut Diagnostics::strict(),
+        )?;
+        assert_eq!(fonts.hit_circle.prefix, "default");
+        assert_eq!(fonts.hit_circle.overlap, -2);
+        assert_eq!(fonts.combo.prefix, "score");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_font_prefix_paths() {
+        let mut props = Properties::new();
+        props.insert("ScorePrefix".to_string(), "fonts\\score".to_string());
+        props.insert("ComboPrefix".to_string(), ".\\fonts//combo\\".to_string());
+        props.insert("HitCirclePrefix".to_string(), "".to_string());
+
+        let fonts = parse_fonts(&props, &mut Diagnostics::strict()).unwrap();
+        assert_eq!(fonts.score.prefix, "fonts/score");
+        assert_eq!(fonts.combo.prefix, "fonts/combo");
+        assert_eq!(fonts.hit_circle.prefix, "default");
+
+        assert_eq!(fonts.score.glyph('0').as_deref(), Some("fonts/score-0"));
+        assert_eq!(fonts.score.glyph(',').as_deref(), Some("fonts/score-comma"));
+        assert_eq!(
+            fonts.score.glyph('%').as_deref(),
+            Some("fonts/score-percent")
+        );
+        assert_eq!(fonts.score.glyph('?'), None);
+        let names = fonts.combo.glyph_names();
+        assert_eq!(names.len(), 14);
+        assert_eq!(names[9], "fonts/combo-9");
+        assert_eq!(names[13], "fonts/combo-x");
+    }
+
+    #[test]
+    fn test_normalize_asset_path() {
+        assert_eq!(normalize_asset_path("receptors\\left"), "receptors/left");
+        assert_eq!(
+            normalize_asset_path(" mania/mania-hit0 "),
+            "mania/mania-hit0"
+        );
+        assert_eq!(normalize_asset_path("./a\\\\b/"), "a/b");
+        assert_eq!(normalize_asset_path(""), "");
+    }
+
+    #[test]
+    fn test_read_catch_the_beat_defaults() -> Result<(), Box<dyn Error>> {
+        let catch = read_catch_the_beat(
+            &open_ini("./resources/test2.ini")?,
+            &mut Diagnostics::strict(),
+        )?;
+        let colors = catch.hyper_dash_colors;
+        assert_eq!(colors.main, RgbColor::new(255, 0, 0));
+        assert_eq!(colors.fruit, None);
+        assert_eq!(colors.after_image, None);
+        assert_eq!(colors.get_fruit_color(), RgbColor::new(255, 0, 0));
+        assert_eq!(colors.get_after_image_color(), RgbColor::new(255, 0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_hyper_dash_fallback_chain() {
+        // Only HyperDash: both derived colours follow it
+        let mut props = Properties::new();
+        props.insert("HyperDash".to_string(), "0,128,255".to_string());
+        let colors = parse_catch_the_beat(&props, &mut Diagnostics::strict())
+            .unwrap()
+            .hyper_dash_colors;
+        assert_eq!(colors.fruit, None);
+        assert_eq!(colors.get_fruit_color(), RgbColor::new(0, 128, 255));
+        assert_eq!(colors.get_after_image_color(), RgbColor::new(0, 128, 255));
+
+        // An explicit fruit colour only overrides the fruit
+        props.insert("HyperDashFruit".to_string(), "10,20,30".to_string());
+        let colors = parse_catch_the_beat(&props, &mut Diagnostics::strict())
+            .unwrap()
+            .hyper_dash_colors;
+        assert_eq!(colors.get_fruit_color(), RgbColor::new(10, 20, 30));
+        assert_eq!(colors.get_after_image_color(), RgbColor::new(0, 128, 255));
+
+        // Without HyperDash the overrides still fall back to the default red
+        let mut props = Properties::new();
+        props.insert("HyperDashAfterImage".to_string(), "1,2,3".to_string());
+        let colors = parse_catch_the_beat(&props, &mut Diagnostics::strict())
+            .unwrap()
+            .hyper_dash_colors;
+        assert_eq!(colors.main, RgbColor::new(255, 0, 0));
+        assert_eq!(colors.get_fruit_color(), colors.main);
+        assert_eq!(colors.get_after_image_color(), RgbColor::new(1, 2, 3));
+
+        props.insert("HyperDashFruit".to_string(), "broken".to_string());
+        assert!(matches!(
+            parse_catch_the_beat(&props, &mut Diagnostics::strict()),
+            Err(SkinError::InvalidValue { section, key, .. })
+                if section == "CatchTheBeat" && key == "HyperDashFruit"
+        ));
+    }
+
+    #[test]
+    fn test_parse_gameplay() {
+        let mut props = Properties::new();
+        props.insert("ComboBurstRandom".to_string(), "1".to_string());
+        props.insert(
+            "CustomComboBurstSounds".to_string(),
+            "50, 75,100".to_string(),
+        );
+        props.insert("HitCircleOverlayAboveNumer".to_string(), "0".to_string());
+        props.insert("LayeredHitSounds".to_string(), "0".to_string());
+        props.insert("SliderBallFlip".to_string(), "0".to_string());
+
+        let gameplay = parse_gameplay(&props, &mut Diagnostics::strict()).unwrap();
+        assert!(gameplay.combo_burst_random);
+        assert_eq!(gameplay.custom_combo_burst_sounds, vec![50, 75, 100]);
+        assert!(!gameplay.hit_circle_overlay_above_number);
+        assert!(!gameplay.layered_hit_sounds);
+        assert!(!gameplay.slider_ball_flip);
+
+        props.insert("HitCircleOverlayAboveNumber".to_string(), "1".to_string());
+        assert!(
+            parse_gameplay(&props, &mut Diagnostics::strict())
+                .unwrap()
+                .hit_circle_overlay_above_number
+        );
+    }
+
+    #[test]
+    fn test_parse_metadata_and_spinner() {
+        let mut props = Properties::new();
+        props.insert("AnimationFramerate".to_string(), "30".to_string());
+        props.insert("SpinnerFadePlayfield".to_string(), "1".to_string());
+        props.insert("SpinnerFrequencyModulate".to_string(), "0".to_string());
+        props.insert("SpinnerNoBlink".to_string(), "1".to_string());
+
+        assert_eq!(
+            parse_metadata(&props, &mut Diagnostics::strict())
+                .unwrap()
+                .animation_framerate,
+            30
+        );
+        let spinner = parse_spinner(&props, &mut Diagnostics::strict()).unwrap();
+        assert!(spinner.fade_playfield);
+        assert!(!spinner.frequency_modulate);
+        assert!(spinner.no_blink);
+    }
+}
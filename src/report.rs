@@ -0,0 +1,454 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::animation::FrameRate;
+use crate::elements::{
+    is_referenced, referenced_names, CATCH_ELEMENTS, STANDARD_ELEMENTS, TAIKO_ELEMENTS,
+};
+use crate::error::SkinError;
+use crate::mania::writer::image_entries;
+use crate::osk::OskArchive;
+use crate::parser::normalize_asset_path;
+use crate::resolver::{list_dir, AssetResolver, ResolveOptions};
+use crate::skin::Skin;
+use crate::source::Location;
+
+/// Characters osu! draws with each font: hit circle numbers only have digits.
+const HIT_CIRCLE_GLYPHS: &str = "0123456789";
+const SCORE_GLYPHS: &str = "0123456789,.%x";
+const COMBO_GLYPHS: &str = "0123456789x";
+
+/// Where a checked image is referenced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AssetSection {
+    Standard,
+    Taiko,
+    Catch,
+    /// The font prefixes of `[Fonts]`.
+    Fonts,
+    /// `skin.mania[index]`.
+    Mania {
+        index: usize,
+        key_count: u32,
+    },
+}
+
+/// What is wrong with a referenced image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AssetProblem {
+    /// No file has this name.
+    Missing,
+    /// `found` only matches when case is ignored, which breaks on case-sensitive systems.
+    WrongCase { found: String },
+    /// Files have the name but an extension osu! does not load, e.g. `hit0.jpeg`.
+    WrongExtension { found: Vec<String> },
+}
+
+/// An image that will not load as written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MissingAsset {
+    /// The key naming the image (`NoteImage0L`, `ScorePrefix`), or the element name for
+    /// the fixed standard, taiko and catch elements.
+    pub key: String,
+    /// The name looked up, without extension.
+    pub name: String,
+    pub problem: AssetProblem,
+    /// Where `key` was written, for keys of skin.ini.
+    pub location: Option<Location>,
+}
+
+/// The images of a skin that do not load as written, see `Skin::missing_assets`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MissingAssets {
+    /// Problems by section, in the order the images are checked.
+    pub sections: BTreeMap<AssetSection, Vec<MissingAsset>>,
+}
+
+impl MissingAssets {
+    /// Every problem, section by section.
+    pub fn iter(&self) -> impl Iterator<Item = (AssetSection, &MissingAsset)> {
+        self.sections
+            .iter()
+            .flat_map(|(section, assets)| assets.iter().map(move |asset| (*section, asset)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.sections.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    fn check(
+        &mut self,
+        assets: &AssetResolver,
+        section: AssetSection,
+        key: &str,
+        name: &str,
+        location: Option<Location>,
+    ) {
+        let Some(problem) = problem(assets, name) else {
+            return;
+        };
+        self.sections
+            .entry(section)
+            .or_default()
+            .push(MissingAsset {
+                key: key.to_string(),
+                name: name.to_string(),
+                problem,
+                location,
+            });
+    }
+}
+
+impl Skin {
+    /// Checks every image the skin names against the files of `assets`: the images of
+    /// each `[Mania]` block, the font glyphs and the standard, taiko and catch elements.
+    ///
+    /// Elements left out fall back to the default skin in game, so a `Missing` entry of
+    /// `Standard`, `Taiko` or `Catch` is not always a mistake.
+    pub fn missing_assets(&self, assets: &AssetResolver) -> MissingAssets {
+        let mut report = MissingAssets::default();
+        for (section, elements) in [
+            (AssetSection::Standard, STANDARD_ELEMENTS),
+            (AssetSection::Taiko, TAIKO_ELEMENTS),
+            (AssetSection::Catch, CATCH_ELEMENTS),
+        ] {
+            for element in elements {
+                report.check(assets, section, element, element, None);
+            }
+        }
+
+        let fonts = &self.fonts;
+        let mut checked = BTreeSet::new();
+        for (key, font, glyphs) in [
+            ("HitCirclePrefix", &fonts.hit_circle, HIT_CIRCLE_GLYPHS),
+            ("ScorePrefix", &fonts.score, SCORE_GLYPHS),
+            ("ComboPrefix", &fonts.combo, COMBO_GLYPHS),
+        ] {
+            let location = self.locate("Fonts", key);
+            for glyph in glyphs.chars().filter_map(|c| font.glyph(c)) {
+                // Combo digits are usually the score ones
+                if checked.insert(glyph.to_lowercase()) {
+                    report.check(assets, AssetSection::Fonts, key, &glyph, location.clone());
+                }
+            }
+        }
+
+        for (index, config) in self.mania.iter().enumerate() {
+            let section = AssetSection::Mania {
+                index,
+                key_count: config.keys.count,
+            };
+            for (key, name) in image_entries(&config.images) {
+                let location = self.locate_mania(index, &key);
+                report.check(assets, section, &key, &name, location);
+            }
+        }
+        report
+    }
+}
+
+/// A file of a skin that osu! never loads.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnusedFile {
+    /// Path relative to the skin folder.
+    pub path: String,
+    /// Size in bytes.
+    pub size: u64,
+}
+
+/// The files of a skin that osu! never loads, see `Skin::unused_files`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UnusedFiles {
+    /// Sorted by path.
+    pub files: Vec<UnusedFile>,
+}
+
+impl UnusedFiles {
+    /// Size of every unused file, in bytes.
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|file| file.size).sum()
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.iter().map(|file| file.path.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+impl Skin {
+    /// The files among `files` (path relative to the skin folder, size in bytes) that
+    /// nothing will load: neither skin.ini, nor an image or font it names, nor an element
+    /// osu! looks for, see `elements::is_referenced`.
+    pub fn unused_files<S: AsRef<str>>(
+        &self,
+        files: impl IntoIterator<Item = (S, u64)>,
+    ) -> UnusedFiles {
+        let referenced = referenced_names(self);
+        let mut files: Vec<UnusedFile> = files
+            .into_iter()
+            .map(|(path, size)| UnusedFile {
+                path: normalize_asset_path(path.as_ref()),
+                size,
+            })
+            .filter(|file| {
+                !file.path.eq_ignore_ascii_case("skin.ini")
+                    && !is_referenced(&referenced, &file.path)
+            })
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        UnusedFiles { files }
+    }
+
+    /// `unused_files` for every file under the skin folder at `path`.
+    pub fn unused_files_in_dir(&self, path: &str) -> Result<UnusedFiles, SkinError> {
+        Ok(self.unused_files(list_dir(path)?))
+    }
+}
+
+impl OskArchive {
+    /// The files of the archive osu! never loads, see `Skin::unused_files`.
+    pub fn unused_files(&self) -> UnusedFiles {
+        self.skin.unused_files(
+            self.assets
+                .iter()
+                .map(|(path, bytes)| (path, bytes.len() as u64)),
+        )
+    }
+}
+
+/// Why `name` does not load from `assets`, `None` when it does. Animation frames count
+/// as the element.
+fn problem(assets: &AssetResolver, name: &str) -> Option<AssetProblem> {
+    let options = ResolveOptions {
+        high_resolution: true,
+        ..ResolveOptions::default()
+    };
+    if let Some(animation) = assets.animation(name, FrameRate::OneSecond, options) {
+        return animation
+            .frames
+            .into_iter()
+            .find(|frame| !frame.exact_case)
+            .map(|frame| AssetProblem::WrongCase { found: frame.path });
+    }
+    let found: Vec<String> = assets
+        .files_named(name)
+        .into_iter()
+        .map(String::from)
+        .collect();
+    if found.is_empty() {
+        Some(AssetProblem::Missing)
+    } else {
+        Some(AssetProblem::WrongExtension { found })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SkinError;
+
+    #[test]
+    fn test_missing_assets() -> Result<(), SkinError> {
+        let skin = Skin::load("./resources/test.ini")?;
+        let assets = AssetResolver::from_paths([
+            "Arrownote/holdbody.png",
+            "arrownote/Left.png",
+            "Arrownote/up-0.png",
+            "Arrownote/up-1.png",
+            "Arrownote/down.jpeg",
+            "hitcircle@2x.png",
+        ]);
+        let report = skin.missing_assets(&assets);
+        let mania = &report.sections[&AssetSection::Mania {
+            index: 0,
+            key_count: 4,
+        }];
+        let problem = |key: &str| {
+            mania
+                .iter()
+                .find(|asset| asset.key == key)
+                .map(|asset| &asset.problem)
+        };
+
+        assert_eq!(problem("NoteImage0L"), None);
+        assert_eq!(problem("NoteImage1"), None);
+        assert_eq!(
+            problem("NoteImage0"),
+            Some(&AssetProblem::WrongCase {
+                found: "arrownote/Left.png".to_string()
+            })
+        );
+        assert_eq!(
+            problem("NoteImage2"),
+            Some(&AssetProblem::WrongExtension {
+                found: vec!["Arrownote/down.jpeg".to_string()]
+            })
+        );
+        let holdcap = mania
+            .iter()
+            .find(|asset| asset.key == "NoteImage0T")
+            .unwrap();
+        assert_eq!(holdcap.name, "Arrownote\\holdcap");
+        assert_eq!(holdcap.problem, AssetProblem::Missing);
+        assert_eq!(holdcap.location.as_ref().unwrap().line, 37);
+
+        let standard = &report.sections[&AssetSection::Standard];
+        assert!(standard.iter().all(|asset| asset.key != "hitcircle"));
+        assert!(standard.iter().any(|asset| asset.key == "approachcircle"));
+        assert!(report.sections.contains_key(&AssetSection::Fonts));
+        assert_eq!(report.len(), report.iter().count());
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_assets_fonts() -> Result<(), SkinError> {
+        let skin = Skin::load("./resources/test.ini")?;
+        // Hit circle numbers have no symbols, no default-x.png is needed
+        let mut complete: Vec<String> = (0..10)
+            .flat_map(|digit| {
+                [
+                    format!("default-{}.png", digit),
+                    format!("score-{}.png", digit),
+                ]
+            })
+            .collect();
+        for symbol in ["comma", "dot", "percent", "x"] {
+            complete.push(format!("score-{}.png", symbol));
+        }
+        let report = skin.missing_assets(&AssetResolver::from_paths(&complete));
+        assert!(!report.sections.contains_key(&AssetSection::Fonts));
+
+        let partial = complete
+            .iter()
+            .filter(|path| path.as_str() != "score-x.png");
+        let report = skin.missing_assets(&AssetResolver::from_paths(partial));
+        let fonts = &report.sections[&AssetSection::Fonts];
+        assert_eq!(
+            fonts
+                .iter()
+                .map(|asset| (asset.key.as_str(), asset.name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("ScorePrefix", "score-x")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unused_files() -> Result<(), SkinError> {
+        let skin = Skin::load("./resources/test.ini")?;
+        let unused = skin.unused_files([
+            ("Skin.ini", 500),
+            ("Arrownote\\holdbody@2x.png", 10),
+            ("hitcircle.png", 20),
+            ("normal-hitclap.wav", 30),
+            ("mania-key1D-3.png", 40),
+            ("preview.png", 100),
+            ("unused/hitcircle.png", 200),
+            ("notes.txt", 5),
+            // Reported as `WrongExtension` by `missing_assets`
+            ("Arrownote/down.jpeg", 0),
+        ]);
+        assert_eq!(
+            unused.paths().collect::<Vec<_>>(),
+            vec![
+                "Arrownote/down.jpeg",
+                "notes.txt",
+                "preview.png",
+                "unused/hitcircle.png"
+            ]
+        );
+        assert_eq!(unused.total_size(), 305);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unused_files_keeps_loaded_sounds() -> Result<(), SkinError> {
+        let skin = Skin::load("./resources/test.ini")?;
+        let sounds = [
+            "applause.wav",
+            "back-button-click.wav",
+            "check-on.wav",
+            "click-short-confirm.wav",
+            "combobreak.mp3",
+            "comboburst-0.wav",
+            "count1s.wav",
+            "drum-hitclap.wav",
+            "failsound.mp3",
+            "gos.wav",
+            "heartbeat.ogg",
+            "key-press-1.wav",
+            "match-start.wav",
+            "menuhit.wav",
+            "metronomelow.wav",
+            "multi-skipped.wav",
+            "nightcore-kick.wav",
+            "normal-hitnormal.wav",
+            "pause-back-click.wav",
+            "pause-continue-click.wav",
+            "pause-hover.wav",
+            "pause-loop.wav",
+            "pause-retry-click.wav",
+            "readys.wav",
+            "sectionfail.wav",
+            "sectionpass.wav",
+            "seeya.wav",
+            "select-expand.wav",
+            "shutter.wav",
+            "sliderbar.wav",
+            "soft-slidertick.wav",
+            "spinnerbonus.wav",
+            "spinnerspin.wav",
+            "taiko-normal-hitclap.wav",
+            "welcome_piano.mp3",
+            "whoosh.wav",
+        ];
+        // Every sound family is covered
+        for prefix in crate::elements::SOUND_PREFIXES {
+            assert!(
+                sounds.iter().any(|sound| sound.starts_with(prefix)),
+                "{}",
+                prefix
+            );
+        }
+        let files = sounds
+            .iter()
+            .chain(&["target.png", "targetoverlay-0.png", "voice.wav"])
+            .map(|path| (*path, 1));
+        let unused = skin.unused_files(files);
+        assert_eq!(unused.paths().collect::<Vec<_>>(), vec!["voice.wav"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unused_files_in_dir() -> Result<(), SkinError> {
+        let dir = std::env::temp_dir().join(format!("rosu-skin-unused-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("extras"))?;
+        std::fs::write(dir.join("skin.ini"), b"[General]\nName: Dir\n")?;
+        std::fs::write(dir.join("hit300.png"), b"hit")?;
+        std::fs::write(dir.join("extras").join("source.psd"), b"layers")?;
+
+        let path = dir.to_str().unwrap();
+        let skin = Skin::load(&dir.join("skin.ini").to_string_lossy())?;
+        let unused = skin.unused_files_in_dir(path);
+        std::fs::remove_dir_all(&dir)?;
+        let unused = unused?;
+        assert_eq!(
+            unused.files,
+            vec![UnusedFile {
+                path: "extras/source.psd".to_string(),
+                size: 6,
+            }]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use crate::assets::AssetStore;
+use crate::error::SkinError;
+use crate::parser::normalize_asset_path;
+
+/// Extensions osu! tries for an image, in order.
+pub const LOOKUP_EXTENSIONS: &[&str] = &["png", "jpg"];
+
+/// How `AssetResolver::resolve` looks for a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResolveOptions {
+    /// Try the `@2x` variant of each extension before the normal one.
+    pub high_resolution: bool,
+    /// Extensions to probe, in order, see `LOOKUP_EXTENSIONS`.
+    pub extensions: &'static [&'static str],
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        Self {
+            high_resolution: false,
+            extensions: LOOKUP_EXTENSIONS,
+        }
+    }
+}
+
+/// The file an asset name resolved to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedAsset {
+    /// Path of the file in the skin, as stored.
+    pub path: String,
+    /// The candidate that matched, e.g. `mania/mania-hit0@2x.png`.
+    pub candidate: String,
+    /// Whether the `@2x` variant won.
+    pub high_resolution: bool,
+    /// Whether the file has the same case as the candidate. osu! on Windows does not
+    /// care, case-sensitive systems do.
+    pub exact_case: bool,
+}
+
+/// Maps names written in skin.ini (`Arrownote\holdbody`, `mania-hit0`) to the files of
+/// a skin folder or archive, like osu! does: separators and case do not matter and the
+/// extension is probed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AssetResolver {
+    paths: BTreeSet<String>,
+    /// Lowercased path to the first path with that spelling.
+    index: BTreeMap<String, String>,
+}
+
+impl AssetResolver {
+    /// A resolver over `paths`, relative to the skin folder.
+    pub fn from_paths<S: AsRef<str>>(paths: impl IntoIterator<Item = S>) -> Self {
+        let mut resolver = Self::default();
+        for path in paths {
+            let path = normalize_asset_path(path.as_ref());
+            resolver
+                .index
+                .entry(path.to_lowercase())
+                .or_insert_with(|| path.clone());
+            resolver.paths.insert(path);
+        }
+        resolver
+    }
+
+    /// A resolver over the files of an archive.
+    pub fn from_store(assets: &AssetStore) -> Self {
+        Self::from_paths(assets.paths())
+    }
+
+    /// A resolver over every file under the skin folder at `path`.
+    pub fn from_dir(path: &str) -> Result<Self, SkinError> {
+        let files = list_dir(path)?;
+        Ok(Self::from_paths(files.into_iter().map(|(path, _)| path)))
+    }
+
+    /// Every file, sorted by path.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.paths.iter().map(String::as_str)
+    }
+
+    /// The file at `path` ignoring case and separators, as stored.
+    pub fn find(&self, path: &str) -> Option<&str> {
+        let path = normalize_asset_path(path);
+        match self.paths.get(&path) {
+            Some(path) => Some(path),
+            None => self.index.get(&path.to_lowercase()).map(String::as_str),
+        }
+    }
+
+    /// Every file named `name` whatever its extension, `@2x` variants included, ignoring
+    /// case and separators.
+    pub fn files_named(&self, name: &str) -> Vec<&str> {
+        let name = normalize_asset_path(name).to_lowercase();
+        self.paths()
+            .filter(|path| {
+                let stem = path.rsplit_once('.').map_or(*path, |(stem, _)| stem);
+                let stem = stem.strip_suffix("@2x").unwrap_or(stem);
+                stem.to_lowercase() == name
+            })
+            .collect()
+    }
+
+    /// The file osu! loads for `name`, trying the candidates of `candidates(name, options)`
+    /// in turn.
+    pub fn resolve(&self, name: &str, options: ResolveOptions) -> Option<ResolvedAsset> {
+        probes(name, options)
+            .into_iter()
+            .find_map(|(candidate, high_resolution)| {
+                let path = self.find(&candidate)?.to_string();
+                Some(ResolvedAsset {
+                    exact_case: path == candidate,
+                    path,
+                    candidate,
+                    high_resolution,
+                })
+            })
+    }
+}
+
+/// The files osu! tries for `name`, in order: `@2x` first when asked for, each with every
+/// extension. Empty for an empty name.
+pub fn candidates(name: &str, options: ResolveOptions) -> Vec<String> {
+    probes(name, options)
+        .into_iter()
+        .map(|(candidate, _)| candidate)
+        .collect()
+}
+
+/// `candidates` with whether each one is a `@2x` variant.
+fn probes(name: &str, options: ResolveOptions) -> Vec<(String, bool)> {
+    let name = normalize_asset_path(name);
+    if name.is_empty() {
+        return Vec::new();
+    }
+    let variants: &[bool] = if options.high_resolution {
+        &[true, false]
+    } else {
+        &[false]
+    };
+    let mut probes = Vec::new();
+    for &high_resolution in variants {
+        let suffix = if high_resolution { "@2x" } else { "" };
+        for extension in options.extensions {
+            probes.push((format!("{}{}.{}", name, suffix, extension), high_resolution));
+        }
+    }
+    probes
+}
+
+/// Every file under the folder at `path`, relative to it, with its size in bytes.
+pub(crate) fn list_dir(path: &str) -> Result<Vec<(String, u64)>, SkinError> {
+    let mut files = Vec::new();
+    walk(Path::new(path), "", &mut files)?;
+    Ok(files)
+}
+
+/// Adds the files under `dir` to `files`, prefixed with `prefix`.
+fn walk(dir: &Path, prefix: &str, files: &mut Vec<(String, u64)>) -> Result<(), SkinError> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let path = match prefix {
+            "" => name,
+            prefix => format!("{}/{}", prefix, name),
+        };
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            walk(&entry.path(), &path, files)?;
+        } else {
+            files.push((path, metadata.len()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HD: ResolveOptions = ResolveOptions {
+        high_resolution: true,
+        extensions: LOOKUP_EXTENSIONS,
+    };
+
+    #[test]
+    fn test_candidates() {
+        assert_eq!(
+            candidates("mania\\hit0", HD),
+            vec![
+                "mania/hit0@2x.png",
+                "mania/hit0@2x.jpg",
+                "mania/hit0.png",
+                "mania/hit0.jpg"
+            ]
+        );
+        assert_eq!(
+            candidates("mania-key1", ResolveOptions::default()),
+            vec!["mania-key1.png", "mania-key1.jpg"]
+        );
+        assert!(candidates(" ", HD).is_empty());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let resolver = AssetResolver::from_paths([
+            "Arrownote/HoldBody.png",
+            "mania-key1.jpg",
+            "mania-key1@2x.jpg",
+            "mania-note1.png",
+        ]);
+
+        let body = resolver
+            .resolve("arrownote\\holdbody", ResolveOptions::default())
+            .unwrap();
+        assert_eq!(body.path, "Arrownote/HoldBody.png");
+        assert_eq!(body.candidate, "arrownote/holdbody.png");
+        assert!(!body.exact_case);
+
+        let key = resolver.resolve("mania-key1", HD).unwrap();
+        assert_eq!(key.path, "mania-key1@2x.jpg");
+        assert!(key.high_resolution);
+        assert!(key.exact_case);
+        let key = resolver
+            .resolve("mania-key1", ResolveOptions::default())
+            .unwrap();
+        assert_eq!(key.path, "mania-key1.jpg");
+
+        // Without a @2x file, the normal one
+        let note = resolver.resolve("mania-note1", HD).unwrap();
+        assert_eq!(note.path, "mania-note1.png");
+        assert!(!note.high_resolution);
+
+        assert!(resolver.resolve("mania-note2", HD).is_none());
+        assert!(resolver.resolve("", HD).is_none());
+    }
+
+    #[test]
+    fn test_files_named() {
+        let resolver = AssetResolver::from_paths([
+            "Mania/Hit0.jpeg",
+            "mania/hit0@2x.bmp",
+            "mania/hit0-0.png",
+            "mania/hit00.png",
+        ]);
+        assert_eq!(
+            resolver.files_named("mania\\hit0"),
+            vec!["Mania/Hit0.jpeg", "mania/hit0@2x.bmp"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_prefers_exact_case() {
+        let resolver = AssetResolver::from_paths(["Hit0.png", "hit0.png"]);
+        let hit = resolver.resolve("hit0", ResolveOptions::default()).unwrap();
+        assert_eq!(hit.path, "hit0.png");
+        assert!(hit.exact_case);
+    }
+
+    #[test]
+    fn test_from_dir() -> Result<(), SkinError> {
+        let dir = std::env::temp_dir().join(format!("rosu-skin-resolver-{}", std::process::id()));
+        fs::create_dir_all(dir.join("Mania"))?;
+        fs::write(dir.join("Mania").join("Hit300.png"), b"hit")?;
+        fs::write(dir.join("skin.ini"), b"")?;
+
+        let resolver = AssetResolver::from_dir(dir.to_str().unwrap())?;
+        fs::remove_dir_all(&dir)?;
+        assert_eq!(
+            resolver.paths().collect::<Vec<_>>(),
+            vec!["Mania/Hit300.png", "skin.ini"]
+        );
+        assert_eq!(
+            resolver
+                .resolve("mania\\hit300", ResolveOptions::default())
+                .map(|asset| asset.path),
+            Some("Mania/Hit300.png".to_string())
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,631 @@
+use std::borrow::Cow;
+use std::io::Read;
+use std::str::FromStr;
+
+use crate::diagnostic::{Diagnostic, Diagnostics, ParseMode};
+use crate::encoding::decode;
+use crate::error::SkinError;
+use crate::ini::{report_syntax, Ini};
+use crate::mania::parser::{default_config, parse_mania_section};
+use crate::mania::structs::ManiaConfig;
+use crate::parser::{read_catch_the_beat, read_colours, read_fonts, read_general};
+use crate::source::{Location, SourceMap};
+use crate::structs::{CatchTheBeat, Colours, Fonts, General};
+use crate::writer::{ToIni, WriteOptions};
+
+/// A parsed skin.ini, one field per section.
+///
+/// With the `serde` feature, a skin (de)serializes as an object with the fields below,
+/// named as in Rust. `resources/snapshots/` has the JSON of the sample skins, and
+/// `resources/skin.schema.json` its JSON Schema (`schemars` feature, see `json_schema`).
+///
+/// - Colours of the section structs are `{"r", "g", "b"}` objects, `[Mania]` colours are
+///   `[r, g, b]` or `[r, g, b, a]` arrays. Optional colours are `null` when unset.
+/// - `colours.combo` maps the `N` of `ComboN` (as a string) to its colour.
+/// - `mania` is an array of blocks with their per-column lists, in column order.
+/// - Every section is required, but missing fields inside them take osu!'s defaults.
+///   Values whose defaults depend on where they are, the entries of `fonts` and the
+///   `[Mania]` blocks, must be complete.
+/// - Where entries were written (`source_map`) is not part of the JSON.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Skin {
+    pub general: General,
+    pub colours: Colours,
+    pub fonts: Fonts,
+    pub catch_the_beat: CatchTheBeat,
+    /// Every `[Mania]` block, in file order.
+    pub mania: Vec<ManiaConfig>,
+    /// Where each entry was written, empty when the skin was not read from a file.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub source_map: SourceMap,
+    /// Occurrence of the `[Mania]` section each entry of `mania` was parsed from.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    mania_sections: Vec<usize>,
+}
+
+impl Skin {
+    /// Reads and parses the skin.ini at `path`, failing on the first invalid entry.
+    ///
+    /// This is stricter than osu!: skins the game loads, such as
+    /// `resources/test2.ini` with its `NoteBodyStyle: stretch`, fail here. Use
+    /// `load_lenient` to read a skin the way the game does.
+    pub fn load(path: &str) -> Result<Self, SkinError> {
+        let bytes = std::fs::read(path)?;
+        Ok(Self::parse_bytes(&bytes, Some(path), ParseMode::Strict)?.0)
+    }
+
+    /// Reads the skin.ini at `path` the way osu! does, returning what had to be fixed up.
+    ///
+    /// Only I/O errors are fatal.
+    pub fn load_lenient(path: &str) -> Result<(Self, Vec<Diagnostic>), SkinError> {
+        let bytes = std::fs::read(path)?;
+        Self::parse_bytes(&bytes, Some(path), ParseMode::Lenient)
+    }
+
+    /// Parses a skin.ini already in memory, e.g. an upload body, detecting its encoding.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SkinError> {
+        Ok(Self::parse_bytes(bytes, None, ParseMode::Strict)?.0)
+    }
+
+    /// Lenient version of `from_bytes`, see `load_lenient`.
+    pub fn from_bytes_lenient(bytes: &[u8]) -> Result<(Self, Vec<Diagnostic>), SkinError> {
+        Self::parse_bytes(bytes, None, ParseMode::Lenient)
+    }
+
+    /// Reads a whole skin.ini from `reader`, e.g. an archive entry.
+    pub fn from_reader(mut reader: impl Read) -> Result<Self, SkinError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub(crate) fn parse_bytes(
+        bytes: &[u8],
+        path: Option<&str>,
+        mode: ParseMode,
+    ) -> Result<(Self, Vec<Diagnostic>), SkinError> {
+        let (content, encoding) = decode(bytes);
+        let mut source_map = SourceMap::scan(&content).with_encoding(encoding);
+        if let Some(path) = path {
+            source_map = source_map.with_file(path);
+        }
+        Self::parse_str(&content, source_map, mode)
+    }
+
+    /// The pipeline shared by every constructor.
+    fn parse_str(
+        content: &str,
+        source_map: SourceMap,
+        mode: ParseMode,
+    ) -> Result<(Self, Vec<Diagnostic>), SkinError> {
+        let ini = Ini::load_from_str(content);
+        let mut diagnostics = Diagnostics::new(mode).with_source_map(&source_map);
+        report_syntax(content, &mut diagnostics)?;
+        let skin = Self::from_ini(&ini, &mut diagnostics)?;
+        let diagnostics = diagnostics.into_vec();
+        Ok((skin.with_source_map(source_map), diagnostics))
+    }
+
+    pub fn from_ini(ini: &Ini, diagnostics: &mut Diagnostics) -> Result<Self, SkinError> {
+        let general = read_general(ini, diagnostics)?;
+        let colours = read_colours(ini, diagnostics)?;
+        let fonts = read_fonts(ini, diagnostics)?;
+        let catch_the_beat = read_catch_the_beat(ini, diagnostics)?;
+
+        let mut mania = Vec::new();
+        let mut mania_sections = Vec::new();
+        for (occurrence, props) in ini.section_all("Mania").enumerate() {
+            diagnostics.enter_section(occurrence);
+            if let Some(config) = parse_mania_section(props, diagnostics)? {
+                mania.push(config);
+                mania_sections.push(occurrence);
+            }
+        }
+
+        Ok(Self {
+            general,
+            colours,
+            fonts,
+            catch_the_beat,
+            mania,
+            source_map: SourceMap::default(),
+            mania_sections,
+        })
+    }
+
+    /// The `[Mania]` configuration osu! plays `key_count` keys with.
+    ///
+    /// When several blocks have the same `Keys`, the last one wins. Without any, this is
+    /// the game's default layout for that key count, see `default_config`. Either way the
+    /// images the block leaves out have the default skin's names, see `effective_images`.
+    pub fn mania(&self, key_count: u32) -> Option<Cow<'_, ManiaConfig>> {
+        let Some(config) = self
+            .mania
+            .iter()
+            .rev()
+            .find(|config| config.keys.count == key_count)
+        else {
+            return default_config(key_count).map(Cow::Owned);
+        };
+        let images = config.effective_images();
+        if images == config.images {
+            return Some(Cow::Borrowed(config));
+        }
+        Some(Cow::Owned(ManiaConfig {
+            images,
+            ..config.clone()
+        }))
+    }
+
+    /// The skin as skin.ini text, every section and `[Mania]` block in turn.
+    ///
+    /// The file is regenerated from the parsed values: comments, layout and keys osu!
+    /// does not read are not kept.
+    pub fn to_ini_string(&self) -> String {
+        self.to_ini_string_with(WriteOptions::default())
+    }
+
+    /// Like `to_ini_string`, see `WriteOptions`.
+    pub fn to_ini_string_with(&self, options: WriteOptions) -> String {
+        let mut out = self.general.to_ini_string_with(options);
+        out.push_str(&self.colours.to_ini_string_with(options));
+        out.push_str(&self.fonts.to_ini_string_with(options));
+        out.push_str(&self.catch_the_beat.to_ini_string_with(options));
+        for config in &self.mania {
+            out.push_str(&config.to_ini_string_with(options));
+        }
+        out
+    }
+
+    /// JSON Schema of the JSON described above, with the default of every field that has
+    /// one and the allowed range of styles and colour channels.
+    #[cfg(feature = "schemars")]
+    pub fn json_schema() -> schemars::schema::RootSchema {
+        schemars::schema_for!(Skin)
+    }
+
+    pub fn with_source_map(mut self, source_map: SourceMap) -> Self {
+        self.source_map = source_map;
+        self
+    }
+
+    /// Where `key` of `section` was written, e.g. `locate("General", "Name")`.
+    ///
+    /// For `[Mania]` keys, use `locate_mania`.
+    pub fn locate(&self, section: &str, key: &str) -> Option<Location> {
+        self.source_map.locate(section, 0, key)
+    }
+
+    /// Where `key` of `self.mania[index]` was written.
+    ///
+    /// Blocks osu! skipped are not in `mania`, so `index` is not always the section's rank in the file.
+    pub fn locate_mania(&self, index: usize, key: &str) -> Option<Location> {
+        let occurrence = *self.mania_sections.get(index)?;
+        self.source_map.locate("Mania", occurrence, key)
+    }
+}
+
+impl FromStr for Skin {
+    type Err = SkinError;
+
+    /// Parses skin.ini text, failing on the first invalid entry.
+    fn from_str(content: &str) -> Result<Self, SkinError> {
+        let source_map = SourceMap::scan(content);
+        Ok(Self::parse_str(content, source_map, ParseMode::Strict)?.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::DiagnosticKind;
+    use crate::encoding::TextEncoding;
+    use std::error::Error;
+
+    #[test]
+    fn test_load() -> Result<(), Box<dyn Error>> {
+        let skin = Skin::load("./resources/test.ini")?;
+        assert_eq!(skin.general.metadata.name, "Aggro");
+        assert_eq!(skin.general.metadata.author, "virtual");
+        assert_eq!(skin.mania.len(), 1);
+        assert_eq!(skin.mania[0].keys.count, 4);
+
+        // osu! loads test2.ini, only the lenient parser does too
+        assert!(Skin::load("./resources/test2.ini").is_err());
+        let (skin, diagnostics) = Skin::load_lenient("./resources/test2.ini")?;
+        assert_eq!(skin.general.metadata.name, "YUGEN REMASTERED v1.2");
+        assert_eq!(skin.mania.len(), 5);
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.key == "NoteBodyStyle" && d.kind == DiagnosticKind::InvalidValue));
+        assert_eq!(diagnostics.len(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_invalid_value() {
+        // `NoteBodyStyle` only takes 0, 1 or 2
+        match Skin::load("./resources/test2.ini") {
+            Err(SkinError::InvalidValue {
+                section,
+                key,
+                value,
+                ..
+            }) => {
+                assert_eq!(section, "Mania");
+                assert_eq!(key, "NoteBodyStyle");
+                assert_eq!(value, "stretch");
+            }
+            other => panic!("expected an invalid NoteBodyStyle, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn test_load_lenient() -> Result<(), Box<dyn Error>> {
+        let (skin, diagnostics) = Skin::load_lenient("./resources/test2.ini")?;
+        assert_eq!(skin.general.metadata.name, "YUGEN REMASTERED v1.2");
+        let key_counts: Vec<u32> = skin.mania.iter().map(|m| m.keys.count).collect();
+        assert_eq!(key_counts, vec![4, 5, 6, 7, 8]);
+        assert!(skin
+            .mania
+            .iter()
+            .all(|m| m.behavior.note_body_style.global == 0));
+
+        // One `NoteBodyStyle: stretch` per [Mania] block
+        assert_eq!(diagnostics.len(), 5);
+        assert!(diagnostics.iter().all(|d| d.key == "NoteBodyStyle"
+            && d.value == "stretch"
+            && d.kind == DiagnosticKind::InvalidValue));
+
+        // Each points at its own block
+        let lines: Vec<Option<usize>> = diagnostics.iter().map(|d| d.line).collect();
+        let expected: Vec<Option<usize>> = (0..5)
+            .map(|index| skin.locate_mania(index, "NoteBodyStyle").map(|l| l.line))
+            .collect();
+        assert_eq!(lines, expected);
+        assert!(lines.iter().all(Option::is_some));
+        assert!(lines.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let (_, diagnostics) = Skin::load_lenient("./resources/test.ini")?;
+        assert!(diagnostics.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_locate() -> Result<(), Box<dyn Error>> {
+        let skin = Skin::load("./resources/test.ini")?;
+        let name = skin.locate("General", "Name").unwrap();
+        assert_eq!(name.file, Some("./resources/test.ini".into()));
+        assert_eq!(name.column, 1);
+
+        let keys = skin.locate_mania(0, "Keys").unwrap();
+        assert!(keys.line > name.line);
+        assert_eq!(skin.locate_mania(1, "Keys"), None);
+        assert_eq!(skin.locate("General", "Missing"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_invalid_value_line() {
+        let err = Skin::load("./resources/test2.ini").err().unwrap();
+        let (_, diagnostics) = Skin::load_lenient("./resources/test2.ini").unwrap();
+        assert_eq!(err.line(), diagnostics[0].line);
+        assert!(err.line().is_some());
+    }
+
+    #[test]
+    fn test_load_syntax() -> Result<(), Box<dyn Error>> {
+        let content = "[General]\nName: a\nVersion\n[Mania\n  [Colours]\n";
+        match content.parse::<Skin>() {
+            Err(SkinError::Syntax { line, column, .. }) => assert_eq!((line, column), (3, 1)),
+            other => panic!("expected a syntax error, got {:?}", other.err()),
+        }
+
+        let (skin, diagnostics) = Skin::from_bytes_lenient(content.as_bytes())?;
+        assert_eq!(skin.general.metadata.name, "a");
+        let kinds: Vec<(&str, Option<usize>, &DiagnosticKind)> = diagnostics
+            .iter()
+            .map(|d| (d.key.as_str(), d.line, &d.kind))
+            .collect();
+        // The malformed headers are also unknown keys of [General]
+        assert_eq!(
+            kinds,
+            vec![
+                (
+                    "Version",
+                    Some(3),
+                    &DiagnosticKind::MissingColon { column: 1 }
+                ),
+                (
+                    "[Mania",
+                    Some(4),
+                    &DiagnosticKind::MalformedHeader { column: 1 }
+                ),
+                (
+                    "[Colours]",
+                    Some(5),
+                    &DiagnosticKind::MalformedHeader { column: 3 }
+                ),
+                ("[Mania", Some(4), &DiagnosticKind::UnknownKey),
+                ("[Colours]", Some(5), &DiagnosticKind::UnknownKey),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_encodings() -> Result<(), Box<dyn Error>> {
+        let skin = Skin::load("./resources/encoding/utf16be.ini")?;
+        assert_eq!(skin.source_map.encoding, TextEncoding::Utf16Be);
+        assert_eq!(skin.general.metadata.name, "Café ゆげん");
+        assert_eq!(skin.mania[0].keys.count, 4);
+        assert_eq!(skin.locate("General", "Name").unwrap().line, 2);
+
+        let skin = Skin::load("./resources/encoding/shift_jis.ini")?;
+        assert_eq!(skin.source_map.encoding, TextEncoding::ShiftJis);
+        assert_eq!(skin.general.metadata.name, "ゆげん");
+
+        // The BOM is not part of the first header
+        let skin = Skin::load("./resources/encoding/utf8_bom.ini")?;
+        assert_eq!(skin.general.metadata.author, "rosu");
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_memory() -> Result<(), Box<dyn Error>> {
+        let content = std::fs::read_to_string("./resources/test.ini")?;
+        let from_str: Skin = content.parse()?;
+        let from_bytes = Skin::from_bytes(content.as_bytes())?;
+        let from_reader = Skin::from_reader(std::io::Cursor::new(content.as_bytes()))?;
+        for skin in [&from_str, &from_bytes, &from_reader] {
+            assert_eq!(skin.general.metadata.name, "Aggro");
+            assert_eq!(skin.mania.len(), 1);
+            assert_eq!(skin.source_map.file, None);
+        }
+        assert_eq!(from_str.locate("General", "Name").unwrap().line, 2);
+
+        let bytes = std::fs::read("./resources/encoding/utf16le.ini")?;
+        let skin = Skin::from_reader(bytes.as_slice())?;
+        assert_eq!(skin.general.metadata.name, "Café ゆげん");
+        assert_eq!(skin.source_map.encoding, TextEncoding::Utf16Le);
+
+        let bytes = std::fs::read("./resources/test2.ini")?;
+        assert!(Skin::from_bytes(&bytes).is_err());
+        let (skin, diagnostics) = Skin::from_bytes_lenient(&bytes)?;
+        assert_eq!(skin.mania.len(), 5);
+        assert_eq!(diagnostics.len(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_ini_string() -> Result<(), Box<dyn Error>> {
+        let skin = Skin::load("./resources/test.ini")?;
+        let text = skin.to_ini_string();
+        assert!(text.starts_with("[General]\nName: Aggro\n"));
+
+        let reloaded: Skin = text.parse()?;
+        assert_eq!(reloaded.general.metadata.author, "virtual");
+        assert_eq!(reloaded.mania.len(), 1);
+        assert_eq!(
+            reloaded.mania[0].images.notes.hold_body,
+            skin.mania[0].images.notes.hold_body
+        );
+        Ok(())
+    }
+
+    fn assert_same_sections(skin: &Skin, other: &Skin) {
+        assert_eq!(skin.general, other.general);
+        assert_eq!(skin.colours, other.colours);
+        assert_eq!(skin.fonts, other.fonts);
+        assert_eq!(skin.catch_the_beat, other.catch_the_beat);
+        assert_eq!(skin.mania, other.mania);
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<(), Box<dyn Error>> {
+        let omit_defaults = WriteOptions {
+            omit_defaults: true,
+        };
+        let skin = Skin::load("./resources/test.ini")?;
+        // test2.ini has invalid values, osu! (and the lenient mode) replaces them
+        let (skin2, _) = Skin::load_lenient("./resources/test2.ini")?;
+
+        for skin in [skin, skin2] {
+            for options in [WriteOptions::default(), omit_defaults] {
+                let text = skin.to_ini_string_with(options);
+                let reloaded: Skin = text.parse()?;
+                assert_same_sections(&skin, &reloaded);
+                // Writing is stable once the values went through the writer
+                assert_eq!(reloaded.to_ini_string_with(options), text);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_omit_defaults() -> Result<(), Box<dyn Error>> {
+        let options = WriteOptions {
+            omit_defaults: true,
+        };
+        let skin: Skin = "[Mania]\nKeys: 4\n".parse()?;
+        assert_eq!(
+            skin.to_ini_string_with(options),
+            "[General]\n\n[Colours]\n\n[Fonts]\n\n[CatchTheBeat]\n\n[Mania]\nKeys: 4\n\n"
+        );
+
+        let skin = Skin::load("./resources/test.ini")?;
+        let full = skin.to_ini_string();
+        let short = skin.to_ini_string_with(options);
+        assert!(short.len() < full.len());
+        assert!(short.contains("Name: Aggro\n"));
+        assert!(!short.contains("CursorCentre"));
+        Ok(())
+    }
+
+    /// Contents of the committed snapshot at `path`, first rewritten with `actual` when
+    /// `UPDATE_SNAPSHOTS` is set. A missing snapshot fails the test.
+    #[cfg(feature = "serde")]
+    fn read_snapshot(path: &str, actual: &str) -> Result<String, Box<dyn Error>> {
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(path, actual)?;
+        }
+        std::fs::read_to_string(path).map_err(|err| {
+            format!(
+                "{}: {}, run with UPDATE_SNAPSHOTS=1 to create it",
+                path, err
+            )
+            .into()
+        })
+    }
+
+    /// Compares the JSON of `skin` with `resources/snapshots/<name>.json`, see
+    /// `read_snapshot`.
+    #[cfg(feature = "serde")]
+    fn assert_json_snapshot(skin: &Skin, name: &str) -> Result<(), Box<dyn Error>> {
+        let path = format!("./resources/snapshots/{}.json", name);
+        let json = serde_json::to_string_pretty(skin)? + "\n";
+        assert_eq!(json, read_snapshot(&path, &json)?, "{}", path);
+
+        let deserialized: Skin = serde_json::from_str(&json)?;
+        assert_same_sections(skin, &deserialized);
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_snapshots() -> Result<(), Box<dyn Error>> {
+        assert_json_snapshot(&Skin::load("./resources/test.ini")?, "test")?;
+        assert_json_snapshot(&Skin::load_lenient("./resources/test2.ini")?.0, "test2")?;
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_defaults() -> Result<(), Box<dyn Error>> {
+        let skin: Skin = serde_json::from_str(
+            r#"{
+                "general": {"metadata": {"name": "Aggro"}},
+                "colours": {"combo": {"2": {"r": 1, "g": 2, "b": 3}}},
+                "fonts": {},
+                "catch_the_beat": {},
+                "mania": []
+            }"#,
+        )?;
+        assert_eq!(skin.general.metadata.name, "Aggro");
+        assert_eq!(skin.general.metadata.version, "latest");
+        assert_eq!(
+            skin.colours.combo.ordered(),
+            vec![crate::structs::RgbColor::new(1, 2, 3)]
+        );
+        assert_eq!(skin.fonts, Fonts::default());
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_partial_sections() -> Result<(), Box<dyn Error>> {
+        use crate::structs::{FontConfig, RgbColor};
+
+        let json = r#"{
+            "general": {"cursor": {"expand": false}, "gameplay": {"layered_hit_sounds": false}},
+            "colours": {"interface": {}},
+            "fonts": {"score": {"prefix": "num", "overlap": 3}},
+            "catch_the_beat": {"hyper_dash_colors": {"fruit": {"r": 1, "g": 2, "b": 3}}},
+            "mania": []
+        }"#;
+        let skin: Skin = serde_json::from_str(json)?;
+        let defaults = General::default();
+        assert!(!skin.general.cursor.expand);
+        assert_eq!(skin.general.cursor.centre, defaults.cursor.centre);
+        assert!(!skin.general.gameplay.layered_hit_sounds);
+        assert_eq!(
+            skin.general.gameplay.allow_slider_ball_tint,
+            defaults.gameplay.allow_slider_ball_tint
+        );
+        assert_eq!(skin.colours, Colours::default());
+        assert_eq!(skin.fonts.score, FontConfig::new("num", 3));
+        assert_eq!(skin.fonts.hit_circle, Fonts::default().hit_circle);
+        let hyper_dash = &skin.catch_the_beat.hyper_dash_colors;
+        assert_eq!(hyper_dash.main, RgbColor::new(255, 0, 0));
+        assert_eq!(hyper_dash.fruit, Some(RgbColor::new(1, 2, 3)));
+
+        // The default overlap depends on the font, so a font entry must be complete
+        let json = json.replace(r#", "overlap": 3"#, "");
+        assert!(serde_json::from_str::<Skin>(&json).is_err());
+        Ok(())
+    }
+
+    /// The committed schema must match the structs, run with `UPDATE_SNAPSHOTS` set to
+    /// regenerate it.
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_json_schema() -> Result<(), Box<dyn Error>> {
+        let path = "./resources/skin.schema.json";
+        let schema = serde_json::to_value(Skin::json_schema())?;
+        let json = serde_json::to_string_pretty(&schema)? + "\n";
+        assert_eq!(json, read_snapshot(path, &json)?, "{} is out of date", path);
+
+        let definitions = &schema["definitions"];
+        assert_eq!(
+            definitions["SkinMetadata"]["properties"]["name"]["default"],
+            "Unknown"
+        );
+        assert_eq!(
+            definitions["General"]["properties"]["cursor"]["default"]["centre"],
+            true
+        );
+        assert_eq!(
+            definitions["SpecialStyle"]["properties"]["style_type"]["maximum"],
+            2.0
+        );
+        let body_style = &definitions["NoteBodyStyle"]["properties"];
+        assert_eq!(body_style["global"]["maximum"], 2.0);
+        assert_eq!(body_style["per_column"]["items"]["maximum"], 2.0);
+        assert_eq!(definitions["RgbColor"]["properties"]["g"]["maximum"], 255.0);
+        let columns = &definitions["ManiaColours"]["properties"]["columns"];
+        assert_eq!(columns["items"]["items"]["maximum"], 255.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mania_lookup() -> Result<(), Box<dyn Error>> {
+        let (skin, _) = Skin::load_lenient("./resources/test2.ini")?;
+        let config = skin.mania(7).unwrap();
+        assert_eq!(config.positions, skin.mania[3].positions);
+        assert_eq!(config.images, skin.mania[3].effective_images());
+        assert!(config
+            .images
+            .notes
+            .hold_tail
+            .iter()
+            .all(|name| !name.is_empty()));
+
+        let config = skin.mania(9).unwrap();
+        assert!(matches!(config, Cow::Owned(_)));
+        assert_eq!(config.keys.count, 9);
+        assert!(skin.mania(0).is_none());
+        assert!(skin.mania(19).is_none());
+
+        let skin: Skin =
+            "[Mania]\nKeys: 4\nHitPosition: 400\n[Mania]\nKeys: 4\nHitPosition: 420\n".parse()?;
+        assert_eq!(skin.mania(4).unwrap().positions.hit_position, 420);
+
+        // Configured and default blocks name their images alike
+        let default: Skin = "[General]\nName: Empty\n".parse()?;
+        assert_eq!(
+            default.mania(4).unwrap().images,
+            skin.mania(4).unwrap().images
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        assert!(matches!(
+            Skin::load("./resources/does-not-exist.ini"),
+            Err(SkinError::Io(_))
+        ));
+    }
+}
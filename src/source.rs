@@ -0,0 +1,175 @@
+use std::path::{Path, PathBuf};
+
+use crate::encoding::TextEncoding;
+use crate::ini::group_sections;
+use crate::lexer::tokenize;
+
+/// Position of an entry in the original file, both 1-based.
+///
+/// `column` counts characters, not bytes, and points at the first character of the key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A `Span` together with the file it points into.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Where a section header and each of its keys were written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionSpans {
+    pub name: String,
+    /// First header of the section, `None` when it is never written and only holds keys
+    /// from before the first header.
+    pub header: Option<Span>,
+    entries: Vec<(String, Span)>,
+}
+
+impl SectionSpans {
+    /// Span of the last `key` of the section, the one osu! keeps.
+    pub fn get(&self, key: &str) -> Option<Span> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, span)| *span)
+    }
+
+    /// Every key of the section in file order, duplicates included.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Span)> {
+        self.entries.iter().map(|(key, span)| (key.as_str(), *span))
+    }
+}
+
+/// Line and column of every section and key of a skin.ini.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    pub file: Option<PathBuf>,
+    /// Encoding the file was decoded from, UTF-8 for text that was never bytes.
+    pub encoding: TextEncoding,
+    pub sections: Vec<SectionSpans>,
+}
+
+impl SourceMap {
+    /// Records the position of every section header and key of `content`, grouped into
+    /// sections the same way `Ini` does.
+    pub fn scan(content: &str) -> Self {
+        let sections = group_sections(&tokenize(content))
+            .into_iter()
+            .map(|group| SectionSpans {
+                name: group.name.to_string(),
+                header: group.header,
+                entries: group
+                    .entries
+                    .into_iter()
+                    .map(|(key, _, span)| (key.to_string(), span))
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            file: None,
+            encoding: TextEncoding::default(),
+            sections,
+        }
+    }
+
+    pub fn with_file(mut self, file: impl AsRef<Path>) -> Self {
+        self.file = Some(file.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn with_encoding(mut self, encoding: TextEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// The `occurrence`th section called `name`, counting from 0.
+    pub fn section(&self, name: &str, occurrence: usize) -> Option<&SectionSpans> {
+        self.sections
+            .iter()
+            .filter(|section| section.name == name)
+            .nth(occurrence)
+    }
+
+    /// Location of `key` in the `occurrence`th section called `name`.
+    pub fn locate(&self, name: &str, occurrence: usize, key: &str) -> Option<Location> {
+        let span = self.section(name, occurrence)?.get(key)?;
+        Some(Location {
+            file: self.file.clone(),
+            line: span.line,
+            column: span.column,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan() {
+        let map = SourceMap::scan(
+            "\u{feff}// header\n[General]\nName: x\n\n[Mania]\n  Keys: 4\n  HitPosition: 400\n\
+             [Mania]\nKeys: 7\n//HitPosition: 1\nHitPosition: 420\nHitPosition: 430\n",
+        );
+        assert_eq!(map.sections.len(), 3);
+        assert_eq!(map.sections[0].header, Some(Span { line: 2, column: 1 }));
+        assert_eq!(
+            map.section("General", 0).unwrap().get("Name").unwrap().line,
+            3
+        );
+
+        let first = map.section("Mania", 0).unwrap();
+        assert_eq!(first.get("HitPosition"), Some(Span { line: 7, column: 3 }));
+        assert_eq!(first.get("Name"), None);
+
+        // The last duplicate is the one that counts
+        let second = map.section("Mania", 1).unwrap();
+        assert_eq!(
+            second.get("HitPosition"),
+            Some(Span {
+                line: 12,
+                column: 1
+            })
+        );
+        assert_eq!(second.iter().count(), 3);
+        assert!(map.section("Mania", 2).is_none());
+    }
+
+    #[test]
+    fn test_scan_implicit_general() {
+        let map = SourceMap::scan(
+            "Name: x
+[Fonts]
+[General]
+Author: y
+",
+        );
+        let general = map.section("General", 0).unwrap();
+        assert_eq!(general.header, Some(Span { line: 3, column: 1 }));
+        assert_eq!(general.get("Name"), Some(Span { line: 1, column: 1 }));
+        assert_eq!(general.get("Author"), Some(Span { line: 4, column: 1 }));
+        assert!(map.section("General", 1).is_none());
+    }
+
+    #[test]
+    fn test_locate() {
+        let map = SourceMap::scan("[Fonts]\n ScorePrefix: score\n").with_file("skin.ini");
+        assert_eq!(
+            map.locate("Fonts", 0, "ScorePrefix"),
+            Some(Location {
+                file: Some(PathBuf::from("skin.ini")),
+                line: 2,
+                column: 2,
+            })
+        );
+        assert_eq!(map.locate("Fonts", 0, "ComboPrefix"), None);
+    }
+}
@@ -1,3 +1,9 @@
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct General {
     pub metadata: SkinMetadata,
     pub cursor: CursorConfig,
@@ -5,6 +11,10 @@ pub struct General {
     pub gameplay: GameplayConfig,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct SkinMetadata {
     pub name: String,
     pub author: String,
@@ -12,6 +22,10 @@ pub struct SkinMetadata {
     pub animation_framerate: i32,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct CursorConfig {
     pub centre: bool,
     pub expand: bool,
@@ -19,12 +33,20 @@ pub struct CursorConfig {
     pub trail_rotate: bool,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct SpinnerConfig {
     pub fade_playfield: bool,
     pub frequency_modulate: bool,
     pub no_blink: bool,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct GameplayConfig {
     pub allow_slider_ball_tint: bool,
     pub combo_burst_random: bool,
@@ -34,17 +56,6 @@ pub struct GameplayConfig {
     pub slider_ball_flip: bool,
 }
 
-impl Default for General {
-    fn default() -> Self {
-        Self {
-            metadata: SkinMetadata::default(),
-            cursor: CursorConfig::default(),
-            spinner: SpinnerConfig::default(),
-            gameplay: GameplayConfig::default(),
-        }
-    }
-}
-
 impl Default for SkinMetadata {
     fn default() -> Self {
         Self {
@@ -90,10 +101,15 @@ impl Default for GameplayConfig {
     }
 }
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RgbColor {
+    #[cfg_attr(feature = "schemars", schemars(range(min = 0, max = 255)))]
     pub r: u8,
+    #[cfg_attr(feature = "schemars", schemars(range(min = 0, max = 255)))]
     pub g: u8,
+    #[cfg_attr(feature = "schemars", schemars(range(min = 0, max = 255)))]
     pub b: u8,
 }
 
@@ -103,23 +119,60 @@ impl RgbColor {
     }
 }
 
+impl From<[u8; 3]> for RgbColor {
+    fn from([r, g, b]: [u8; 3]) -> Self {
+        Self { r, g, b }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Colours {
     pub combo: ComboColours,
     pub interface: InterfaceColours,
     pub gameplay: GameplayColours,
 }
 
+/// `ComboN` colours keyed by `N`.
+///
+/// Gaps are kept as written (a skin may only define `Combo1` and `Combo3`),
+/// the game cycles through the defined colours in ascending order.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct ComboColours {
-    pub combo1: RgbColor,
-    pub combo2: RgbColor,
-    pub combo3: RgbColor,
-    pub combo4: RgbColor,
-    pub combo5: Option<RgbColor>,
-    pub combo6: Option<RgbColor>,
-    pub combo7: Option<RgbColor>,
-    pub combo8: Option<RgbColor>,
+    pub colours: BTreeMap<u32, RgbColor>,
+}
+
+impl ComboColours {
+    pub fn get(&self, n: u32) -> Option<RgbColor> {
+        self.colours.get(&n).copied()
+    }
+
+    /// Colours in the order the game cycles through them.
+    pub fn ordered(&self) -> Vec<RgbColor> {
+        self.colours.values().copied().collect()
+    }
+
+    /// Colour used for the combo at `combo_index` (0-based), wrapping around.
+    pub fn for_combo(&self, combo_index: usize) -> Option<RgbColor> {
+        if self.colours.is_empty() {
+            return None;
+        }
+        self.colours
+            .values()
+            .nth(combo_index % self.colours.len())
+            .copied()
+    }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct InterfaceColours {
     pub menu_glow: RgbColor,
     pub song_select_active_text: RgbColor,
@@ -127,6 +180,10 @@ pub struct InterfaceColours {
     pub input_overlay_text: RgbColor,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct GameplayColours {
     pub slider_ball: RgbColor,
     pub slider_border: RgbColor,
@@ -135,27 +192,15 @@ pub struct GameplayColours {
     pub star_break_additive: RgbColor,
 }
 
-impl Default for Colours {
-    fn default() -> Self {
-        Self {
-            combo: ComboColours::default(),
-            interface: InterfaceColours::default(),
-            gameplay: GameplayColours::default(),
-        }
-    }
-}
-
 impl Default for ComboColours {
     fn default() -> Self {
         Self {
-            combo1: RgbColor::new(255, 192, 0),    // Orange
-            combo2: RgbColor::new(0, 202, 0),      // Vert
-            combo3: RgbColor::new(18, 124, 255),   // Bleu
-            combo4: RgbColor::new(242, 24, 57),    // Rouge
-            combo5: None,
-            combo6: None,
-            combo7: None,
-            combo8: None,
+            colours: BTreeMap::from([
+                (1, RgbColor::new(255, 192, 0)),  // Orange
+                (2, RgbColor::new(0, 202, 0)),    // Vert
+                (3, RgbColor::new(18, 124, 255)), // Bleu
+                (4, RgbColor::new(242, 24, 57)),  // Rouge
+            ]),
         }
     }
 }
@@ -183,12 +228,19 @@ impl Default for GameplayColours {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Fonts {
     pub hit_circle: FontConfig,
     pub score: FontConfig,
     pub combo: FontConfig,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct FontConfig {
     pub prefix: String,
     pub overlap: i32,
@@ -214,31 +266,66 @@ impl Default for Fonts {
 }
 
 impl FontConfig {
+    /// Characters a font can have a sprite for, with the suffix of that sprite.
+    pub const GLYPHS: [(char, &'static str); 14] = [
+        ('0', "0"),
+        ('1', "1"),
+        ('2', "2"),
+        ('3', "3"),
+        ('4', "4"),
+        ('5', "5"),
+        ('6', "6"),
+        ('7', "7"),
+        ('8', "8"),
+        ('9', "9"),
+        (',', "comma"),
+        ('.', "dot"),
+        ('%', "percent"),
+        ('x', "x"),
+    ];
+
     pub fn new(prefix: &str, overlap: i32) -> Self {
         Self {
             prefix: String::from(prefix),
             overlap,
         }
     }
+
+    /// Sprite name (without extension) used to draw `c`, e.g. `score-comma`.
+    pub fn glyph(&self, c: char) -> Option<String> {
+        Self::GLYPHS
+            .iter()
+            .find(|(glyph, _)| *glyph == c)
+            .map(|(_, suffix)| format!("{}-{}", self.prefix, suffix))
+    }
+
+    /// Every sprite name this font can load, in `GLYPHS` order.
+    pub fn glyph_names(&self) -> Vec<String> {
+        Self::GLYPHS
+            .iter()
+            .map(|(_, suffix)| format!("{}-{}", self.prefix, suffix))
+            .collect()
+    }
 }
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct CatchTheBeat {
     pub hyper_dash_colors: HyperDashColors,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct HyperDashColors {
     pub main: RgbColor,
     pub fruit: Option<RgbColor>,
     pub after_image: Option<RgbColor>,
 }
 
-impl Default for CatchTheBeat {
-    fn default() -> Self {
-        Self {
-            hyper_dash_colors: HyperDashColors::default(),
-        }
-    }
-}
-
 impl Default for HyperDashColors {
     fn default() -> Self {
         let default_color = RgbColor::new(255, 0, 0);
@@ -259,4 +346,3 @@ impl HyperDashColors {
         self.after_image.unwrap_or(self.main)
     }
 }
-
@@ -0,0 +1,379 @@
+use crate::structs::{CatchTheBeat, Colours, ComboColours, Fonts, General, RgbColor};
+
+/// `Key: Value` lines of a section, in the order they are written.
+pub type Entries = Vec<(String, String)>;
+
+/// How sections are turned back into skin.ini text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Leave out the keys whose value is what osu! uses when the key is absent.
+    pub omit_defaults: bool,
+}
+
+/// A skin.ini section that can be written back.
+pub trait ToIni {
+    /// Name of the section header, without brackets.
+    const SECTION: &'static str;
+
+    /// Every key of the section, defaults included.
+    fn entries(&self) -> Entries;
+
+    /// The entries of the section with no key set.
+    fn default_entries(&self) -> Entries;
+
+    /// `entries`, without the defaults when `options.omit_defaults` is set.
+    fn entries_with(&self, options: WriteOptions) -> Entries {
+        let entries = self.entries();
+        if !options.omit_defaults {
+            return entries;
+        }
+        let defaults = self.default_entries();
+        entries
+            .into_iter()
+            .filter(|entry| !defaults.contains(entry))
+            .collect()
+    }
+
+    /// The section as skin.ini text, header included.
+    fn to_ini_string(&self) -> String {
+        self.to_ini_string_with(WriteOptions::default())
+    }
+
+    fn to_ini_string_with(&self, options: WriteOptions) -> String {
+        let mut out = String::new();
+        write_section(&mut out, Self::SECTION, &self.entries_with(options));
+        out
+    }
+}
+
+impl ToIni for General {
+    const SECTION: &'static str = "General";
+
+    fn entries(&self) -> Entries {
+        general_entries(self)
+    }
+
+    fn default_entries(&self) -> Entries {
+        general_entries(&General::default())
+    }
+}
+
+impl ToIni for Colours {
+    const SECTION: &'static str = "Colours";
+
+    fn entries(&self) -> Entries {
+        colours_entries(self)
+    }
+
+    fn default_entries(&self) -> Entries {
+        colours_entries(&Colours::default())
+    }
+
+    /// Combo colours replace the defaults as a whole, so they are all kept as soon as one
+    /// of them differs.
+    fn entries_with(&self, options: WriteOptions) -> Entries {
+        let entries = self.entries();
+        if !options.omit_defaults {
+            return entries;
+        }
+        let keep_combos = self.combo != ComboColours::default();
+        let defaults = self.default_entries();
+        entries
+            .into_iter()
+            .filter(|entry| {
+                (keep_combos && entry.0.starts_with("Combo")) || !defaults.contains(entry)
+            })
+            .collect()
+    }
+}
+
+impl ToIni for Fonts {
+    const SECTION: &'static str = "Fonts";
+
+    fn entries(&self) -> Entries {
+        fonts_entries(self)
+    }
+
+    fn default_entries(&self) -> Entries {
+        fonts_entries(&Fonts::default())
+    }
+}
+
+impl ToIni for CatchTheBeat {
+    const SECTION: &'static str = "CatchTheBeat";
+
+    fn entries(&self) -> Entries {
+        catch_the_beat_entries(self)
+    }
+
+    fn default_entries(&self) -> Entries {
+        catch_the_beat_entries(&CatchTheBeat::default())
+    }
+}
+
+pub fn general_entries(general: &General) -> Entries {
+    let metadata = &general.metadata;
+    let cursor = &general.cursor;
+    let spinner = &general.spinner;
+    let gameplay = &general.gameplay;
+
+    let mut entries = Entries::new();
+    push(&mut entries, "Name", &metadata.name);
+    push(&mut entries, "Author", &metadata.author);
+    push(&mut entries, "Version", &metadata.version);
+    push(
+        &mut entries,
+        "AnimationFramerate",
+        metadata.animation_framerate,
+    );
+    push(&mut entries, "CursorCentre", flag(cursor.centre));
+    push(&mut entries, "CursorExpand", flag(cursor.expand));
+    push(&mut entries, "CursorRotate", flag(cursor.rotate));
+    push(&mut entries, "CursorTrailRotate", flag(cursor.trail_rotate));
+    push(
+        &mut entries,
+        "SpinnerFadePlayfield",
+        flag(spinner.fade_playfield),
+    );
+    push(
+        &mut entries,
+        "SpinnerFrequencyModulate",
+        flag(spinner.frequency_modulate),
+    );
+    push(&mut entries, "SpinnerNoBlink", flag(spinner.no_blink));
+    push(
+        &mut entries,
+        "AllowSliderBallTint",
+        flag(gameplay.allow_slider_ball_tint),
+    );
+    push(
+        &mut entries,
+        "ComboBurstRandom",
+        flag(gameplay.combo_burst_random),
+    );
+    if !gameplay.custom_combo_burst_sounds.is_empty() {
+        push(
+            &mut entries,
+            "CustomComboBurstSounds",
+            list(&gameplay.custom_combo_burst_sounds),
+        );
+    }
+    push(
+        &mut entries,
+        "HitCircleOverlayAboveNumber",
+        flag(gameplay.hit_circle_overlay_above_number),
+    );
+    push(
+        &mut entries,
+        "LayeredHitSounds",
+        flag(gameplay.layered_hit_sounds),
+    );
+    push(
+        &mut entries,
+        "SliderBallFlip",
+        flag(gameplay.slider_ball_flip),
+    );
+    entries
+}
+
+pub fn colours_entries(colours: &Colours) -> Entries {
+    let interface = &colours.interface;
+    let gameplay = &colours.gameplay;
+
+    let mut entries = Entries::new();
+    for (n, colour) in &colours.combo.colours {
+        push(&mut entries, &format!("Combo{}", n), rgb(*colour));
+    }
+    push(&mut entries, "MenuGlow", rgb(interface.menu_glow));
+    push(
+        &mut entries,
+        "SongSelectActiveText",
+        rgb(interface.song_select_active_text),
+    );
+    push(
+        &mut entries,
+        "SongSelectInactiveText",
+        rgb(interface.song_select_inactive_text),
+    );
+    push(
+        &mut entries,
+        "InputOverlayText",
+        rgb(interface.input_overlay_text),
+    );
+    push(&mut entries, "SliderBall", rgb(gameplay.slider_ball));
+    push(&mut entries, "SliderBorder", rgb(gameplay.slider_border));
+    if let Some(colour) = gameplay.slider_track_override {
+        push(&mut entries, "SliderTrackOverride", rgb(colour));
+    }
+    push(
+        &mut entries,
+        "SpinnerBackground",
+        rgb(gameplay.spinner_background),
+    );
+    push(
+        &mut entries,
+        "StarBreakAdditive",
+        rgb(gameplay.star_break_additive),
+    );
+    entries
+}
+
+pub fn fonts_entries(fonts: &Fonts) -> Entries {
+    let mut entries = Entries::new();
+    for (name, font) in [
+        ("HitCircle", &fonts.hit_circle),
+        ("Score", &fonts.score),
+        ("Combo", &fonts.combo),
+    ] {
+        push(&mut entries, &format!("{}Prefix", name), &font.prefix);
+        push(&mut entries, &format!("{}Overlap", name), font.overlap);
+    }
+    entries
+}
+
+pub fn catch_the_beat_entries(catch_the_beat: &CatchTheBeat) -> Entries {
+    let colours = &catch_the_beat.hyper_dash_colors;
+
+    let mut entries = Entries::new();
+    push(&mut entries, "HyperDash", rgb(colours.main));
+    if let Some(colour) = colours.fruit {
+        push(&mut entries, "HyperDashFruit", rgb(colour));
+    }
+    if let Some(colour) = colours.after_image {
+        push(&mut entries, "HyperDashAfterImage", rgb(colour));
+    }
+    entries
+}
+
+/// Appends `[name]` and its entries to `out`, followed by a blank line.
+pub fn write_section(out: &mut String, name: &str, entries: &[(String, String)]) {
+    out.push('[');
+    out.push_str(name);
+    out.push_str("]\n");
+    for (key, value) in entries {
+        out.push_str(key);
+        out.push_str(": ");
+        out.push_str(value);
+        out.push('\n');
+    }
+    out.push('\n');
+}
+
+pub(crate) fn push(entries: &mut Entries, key: &str, value: impl ToString) {
+    entries.push((key.to_string(), value.to_string()));
+}
+
+/// Booleans are written `1` and `0`, like osu! does.
+pub(crate) fn flag(value: bool) -> &'static str {
+    if value {
+        "1"
+    } else {
+        "0"
+    }
+}
+
+pub(crate) fn list<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn rgb(colour: RgbColor) -> String {
+    format!("{},{},{}", colour.r, colour.g, colour.b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::HyperDashColors;
+
+    fn entry(key: &str, value: &str) -> (String, String) {
+        (key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn test_general_entries() {
+        let mut general = General::default();
+        general.metadata.name = String::from("Aggro");
+        general.gameplay.custom_combo_burst_sounds = vec![50, 75];
+        let entries = general_entries(&general);
+
+        assert_eq!(entries[0], entry("Name", "Aggro"));
+        assert!(entries.contains(&entry("CursorCentre", "1")));
+        assert!(entries.contains(&entry("SpinnerNoBlink", "0")));
+        assert!(entries.contains(&entry("CustomComboBurstSounds", "50,75")));
+        assert!(entries.contains(&entry("AnimationFramerate", "-1")));
+    }
+
+    #[test]
+    fn test_colours_entries() {
+        let mut colours = Colours::default();
+        colours.combo.colours.remove(&2);
+        let entries = colours_entries(&colours);
+
+        let combos: Vec<&str> = entries
+            .iter()
+            .filter(|(key, _)| key.starts_with("Combo"))
+            .map(|(key, _)| key.as_str())
+            .collect();
+        assert_eq!(combos, vec!["Combo1", "Combo3", "Combo4"]);
+        assert!(entries.contains(&entry("MenuGlow", "0,78,155")));
+        assert!(!entries.iter().any(|(key, _)| key == "SliderTrackOverride"));
+    }
+
+    #[test]
+    fn test_fonts_and_catch_the_beat_entries() {
+        let entries = fonts_entries(&Fonts::default());
+        assert_eq!(entries[0], entry("HitCirclePrefix", "default"));
+        assert_eq!(entries[1], entry("HitCircleOverlap", "-2"));
+
+        let catch_the_beat = CatchTheBeat {
+            hyper_dash_colors: HyperDashColors {
+                fruit: Some(RgbColor::new(1, 2, 3)),
+                ..HyperDashColors::default()
+            },
+        };
+        assert_eq!(
+            catch_the_beat_entries(&catch_the_beat),
+            vec![
+                entry("HyperDash", "255,0,0"),
+                entry("HyperDashFruit", "1,2,3")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_omit_defaults() {
+        let options = WriteOptions {
+            omit_defaults: true,
+        };
+        assert_eq!(
+            General::default().to_ini_string_with(options),
+            "[General]\n\n"
+        );
+
+        let mut colours = Colours::default();
+        colours.combo.colours.remove(&4);
+        colours.interface.menu_glow = RgbColor::new(1, 2, 3);
+        // The three remaining combo colours are defaults, but dropping them would bring
+        // back Combo4
+        assert_eq!(
+            colours.to_ini_string_with(options),
+            "[Colours]\nCombo1: 255,192,0\nCombo2: 0,202,0\nCombo3: 18,124,255\n\
+             MenuGlow: 1,2,3\n\n"
+        );
+        assert_eq!(
+            Colours::default().to_ini_string_with(options),
+            "[Colours]\n\n"
+        );
+    }
+
+    #[test]
+    fn test_write_section() {
+        let mut out = String::new();
+        write_section(&mut out, "Fonts", &[entry("ScorePrefix", "score")]);
+        assert_eq!(out, "[Fonts]\nScorePrefix: score\n\n");
+    }
+}